@@ -0,0 +1,33 @@
+//! Makes the items of `#[serde_option(binary_safe)]` readable by formats which aren't
+//! self-describing, and read the fields by position, by never skipping fields.
+//!
+//! The mode is given explicitly, since the format can't be told from the serializer: formats
+//! such as MessagePack or CBOR aren't human-readable, but have `null` and named fields.
+
+use syn::{parse_quote, punctuated::Punctuated, Field, Meta, Token};
+
+/// Removes the `skip_serializing_if` predicates of a field, including a custom one, so that the
+/// field is always written
+pub(crate) fn strip_skip_predicates(field: &mut Field) {
+    field.attrs.retain_mut(|attr| {
+        if !attr.path().is_ident("serde") {
+            return true;
+        }
+        let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        else {
+            return true;
+        };
+        let retained: Punctuated<Meta, Token![,]> = metas
+            .iter()
+            .filter(|meta| !meta.path().is_ident("skip_serializing_if"))
+            .cloned()
+            .collect();
+        if retained.len() == metas.len() {
+            return true;
+        } else if retained.is_empty() {
+            return false;
+        }
+        *attr = parse_quote!(#[serde(#retained)]);
+        true
+    });
+}
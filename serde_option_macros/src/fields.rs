@@ -0,0 +1,356 @@
+//! Generates the `SerdeOptionFields` implementation, which describes the presence rules of all
+//! fields at runtime.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, Attribute, Field, Generics, Ident, LitStr, Token, Type, Variant};
+
+use crate::{attrs_have_attribute, field_has_attribute, get_std_option, skip_meta_value, Presence};
+
+/// A case conversion given with `#[serde(rename_all = "...")]`
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    /// The rule given in the serde attribute `name`, e.g. `rename_all` or `rename_all_fields`.
+    /// Unknown rules are ignored, since serde reports them already.
+    pub(crate) fn from_attrs(attrs: &[Attribute], name: &str) -> Option<Self> {
+        let rule = match serde_attribute_value(attrs, name)?.as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return None,
+        };
+        Some(rule)
+    }
+
+    /// Apply the rule to a variant name, which is assumed to be in `PascalCase`
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_owned(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => lowercase_first(variant),
+            RenameRule::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnake => RenameRule::Snake
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::Kebab => RenameRule::Snake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Apply the rule to a field name, which is assumed to be in `snake_case`
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_owned(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => lowercase_first(&RenameRule::Pascal.apply_to_field(field)),
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_field(field)
+                .replace('_', "-"),
+        }
+    }
+}
+
+/// Lowercase the first character, e.g. for `camelCase`
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// The value of a serde attribute for serialization, e.g. `"name"` for both
+/// `#[serde(rename = "name")]` and `#[serde(rename(serialize = "name"))]`
+pub(crate) fn serde_attribute_value(attrs: &[Attribute], name: &str) -> Option<String> {
+    serde_attribute_value_in(attrs, name, "serialize")
+}
+
+/// The value of a serde attribute for the `direction`, which is either `"serialize"` or
+/// `"deserialize"`
+fn serde_attribute_value_in(attrs: &[Attribute], name: &str, direction: &str) -> Option<String> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident(name) {
+                return skip_meta_value(&meta);
+            }
+            if meta.input.peek(Token![=]) {
+                value = Some(meta.value()?.parse::<LitStr>()?.value());
+                return Ok(());
+            }
+            meta.parse_nested_meta(|nested| {
+                if nested.path.is_ident(direction) {
+                    value = Some(nested.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    skip_meta_value(&nested)
+                }
+            })
+        })
+        .unwrap_or(());
+    }
+    value
+}
+
+/// Every value of a serde attribute which may be repeated, e.g. `#[serde(alias = "...")]`
+fn serde_attribute_values(attrs: &[Attribute], name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                values.push(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                skip_meta_value(&meta)
+            }
+        })
+        .unwrap_or(());
+    }
+    values
+}
+
+/// A field after it has been processed by `process_optional_field`, so that the serde
+/// attributes added by the macro are taken into account
+pub(crate) struct FieldInfo {
+    /// The serialized name
+    pub(crate) name: String,
+    /// The names accepted during deserialization, i.e. the deserialized name followed by the
+    /// aliases of the field
+    pub(crate) deserialize_names: Vec<String>,
+    /// The name of the field in Rust, or its index for tuple fields
+    pub(crate) ident: String,
+    // The field type is only needed for the generated JSON Schema and validation
+    #[cfg_attr(
+        not(any(feature = "json_schema", feature = "validate")),
+        allow(dead_code)
+    )]
+    pub(crate) ty: Type,
+    pub(crate) nullable: bool,
+    pub(crate) required: bool,
+    pub(crate) skipped: bool,
+    pub(crate) has_default: bool,
+    pub(crate) flattened: bool,
+    /// The field is an `Option` which is (de)serialized as its inner value, i.e. it is
+    /// `#[not_required]` but not `#[nullable]`
+    #[cfg_attr(
+        not(any(feature = "json_schema", feature = "validate")),
+        allow(dead_code)
+    )]
+    pub(crate) unwrapped: bool,
+}
+
+impl FieldInfo {
+    /// Collect the information about a field, given the presence rules which apply to it
+    pub(crate) fn new(
+        field: &Field,
+        index: usize,
+        presence: Presence,
+        rename_rule: Option<RenameRule>,
+        container_default: bool,
+    ) -> Self {
+        let ident = field
+            .ident
+            .as_ref()
+            .map_or_else(|| index.to_string(), |ident| ident.unraw().to_string());
+        let renamed = |direction| {
+            serde_attribute_value_in(&field.attrs, "rename", direction).unwrap_or_else(|| {
+                match (&field.ident, rename_rule) {
+                    (Some(_), Some(rule)) => rule.apply_to_field(&ident),
+                    _ => ident.clone(),
+                }
+            })
+        };
+        let name = renamed("serialize");
+        let mut deserialize_names = vec![renamed("deserialize")];
+        deserialize_names.extend(serde_attribute_values(&field.attrs, "alias"));
+
+        let skipped = field_has_attribute(field, "serde", "skip")
+            || field_has_attribute(field, "serde", "skip_deserializing");
+        let has_default = container_default || field_has_attribute(field, "serde", "default");
+        // serde fills in missing `Option` fields with `None`, unless their deserialization is
+        // customized, which includes the `#[nullable]` fields
+        let implicit_default = get_std_option(&field.ty).is_some()
+            && !field_has_attribute(field, "serde", "with")
+            && !field_has_attribute(field, "serde", "deserialize_with");
+
+        Self {
+            name,
+            deserialize_names,
+            ident,
+            ty: field.ty.clone(),
+            nullable: presence.nullable && !skipped,
+            required: !skipped && !has_default && !implicit_default,
+            skipped,
+            has_default,
+            flattened: field_has_attribute(field, "serde", "flatten"),
+            unwrapped: !presence.nullable && presence.not_required,
+        }
+    }
+
+    /// The type of the value of the field, i.e. the `T` of `Option<T>` for fields which are
+    /// (de)serialized as their inner value
+    #[cfg(any(feature = "json_schema", feature = "validate"))]
+    pub(crate) fn value_type(&self, crate_path: &syn::Path) -> TokenStream {
+        let ty = &self.ty;
+        if self.unwrapped {
+            quote!(<#ty as #crate_path::__private::OptionLike>::Inner)
+        } else {
+            quote!(#ty)
+        }
+    }
+
+    /// The `FieldSpec` of the field. Returns `None` for flattened fields, which don't have a
+    /// name of their own.
+    pub(crate) fn spec(&self, crate_path: &syn::Path) -> Option<TokenStream> {
+        if self.flattened {
+            return None;
+        }
+        let Self {
+            name,
+            ident,
+            nullable,
+            required,
+            skipped,
+            has_default,
+            ..
+        } = self;
+        Some(quote! {
+            #crate_path::FieldSpec {
+                name: #name,
+                ident: #ident,
+                nullable: #nullable,
+                required: #required,
+                skipped: #skipped,
+                has_default: #has_default,
+            }
+        })
+    }
+}
+
+/// The serialized name of an enum variant
+pub(crate) fn variant_name(variant: &Variant, rename_rule: Option<RenameRule>) -> String {
+    let ident = variant.ident.unraw().to_string();
+    serde_attribute_value(&variant.attrs, "rename").unwrap_or_else(|| {
+        rename_rule.map_or_else(|| ident.clone(), |rule| rule.apply_to_variant(&ident))
+    })
+}
+
+/// The `VariantSpec` of an enum variant, with the `FieldSpec`s of its fields
+pub(crate) fn variant_spec(
+    variant: &Variant,
+    fields: &[FieldInfo],
+    rename_rule: Option<RenameRule>,
+    crate_path: &syn::Path,
+) -> TokenStream {
+    let ident = variant.ident.unraw().to_string();
+    let name = variant_name(variant, rename_rule);
+    let fields = fields.iter().filter_map(|field| field.spec(crate_path));
+    quote! {
+        #crate_path::VariantSpec {
+            name: #name,
+            ident: #ident,
+            fields: &[#(#fields),*],
+        }
+    }
+}
+
+/// How the variants of an enum are represented, as given with `#[serde(tag = "...")]` and
+/// similar attributes
+#[cfg(any(feature = "json_schema", feature = "validate"))]
+pub(crate) enum Tagging {
+    External,
+    Internal(String),
+    Adjacent(String, String),
+    Untagged,
+}
+
+#[cfg(any(feature = "json_schema", feature = "validate"))]
+impl Tagging {
+    /// Read the tagging from the serde attributes of the container
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Self {
+        if attrs_have_attribute(attrs, "serde", "untagged") {
+            return Tagging::Untagged;
+        }
+        match (
+            serde_attribute_value(attrs, "tag"),
+            serde_attribute_value(attrs, "content"),
+        ) {
+            (Some(tag), Some(content)) => Tagging::Adjacent(tag, content),
+            (Some(tag), None) => Tagging::Internal(tag),
+            _ => Tagging::External,
+        }
+    }
+}
+
+/// Whether a variant is left out of the schema and validation, since it can't be deserialized
+#[cfg(any(feature = "json_schema", feature = "validate"))]
+pub(crate) fn is_skipped_variant(variant: &Variant) -> bool {
+    attrs_have_attribute(&variant.attrs, "serde", "skip")
+        || attrs_have_attribute(&variant.attrs, "serde", "skip_deserializing")
+}
+
+/// Whether a struct fills in all missing fields with `#[serde(default)]` on the container
+pub(crate) fn has_container_default(attrs: &[Attribute]) -> bool {
+    attrs_have_attribute(attrs, "serde", "default")
+}
+
+/// The `SerdeOptionFields` implementation for an item, with the given associated constants
+pub(crate) fn fields_impl(
+    ident: &Ident,
+    generics: &Generics,
+    consts: TokenStream,
+    crate_path: &syn::Path,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #crate_path::SerdeOptionFields for #ident #ty_generics #where_clause {
+            #consts
+        }
+    }
+}
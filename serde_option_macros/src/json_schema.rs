@@ -0,0 +1,138 @@
+//! Generates the `JsonSchema` implementation of `#[serde_option(json_schema)]`, which describes
+//! the presence rules of the fields in a JSON Schema.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Attribute, Fields, Generics, Ident, Path, Variant};
+
+use crate::fields::{serde_attribute_value, variant_name, FieldInfo, RenameRule, Tagging};
+use crate::mentions_any;
+
+/// The expression building the schema of a struct or variant body, where `generator` is in
+/// scope. Returns `None` for unit structs and variants.
+///
+/// Skipped and flattened fields are left out.
+pub(crate) fn fields_schema(
+    fields: &Fields,
+    infos: &[FieldInfo],
+    deny_unknown_fields: bool,
+    crate_path: &Path,
+) -> Option<TokenStream> {
+    let infos = infos.iter().filter(|info| !info.skipped && !info.flattened);
+    match fields {
+        Fields::Unit => None,
+        Fields::Named(_) => {
+            let properties = infos.map(|info| {
+                let name = &info.name;
+                let required = info.required;
+                let schema = field_schema(info, crate_path);
+                quote!((#name, #schema, #required))
+            });
+            Some(quote! {
+                #crate_path::__private::json_schema::object(
+                    ::std::vec![#(#properties),*],
+                    #deny_unknown_fields,
+                )
+            })
+        }
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            infos.map(|info| field_schema(info, crate_path)).next()
+        }
+        Fields::Unnamed(_) => {
+            let items = infos.map(|info| field_schema(info, crate_path));
+            Some(quote! {
+                #crate_path::__private::json_schema::tuple(::std::vec![#(#items),*])
+            })
+        }
+    }
+}
+
+/// The expression building the schema of an enum variant, represented with `tagging`
+pub(crate) fn variant_schema(
+    variant: &Variant,
+    body: Option<TokenStream>,
+    tagging: &Tagging,
+    rename_rule: Option<RenameRule>,
+    crate_path: &Path,
+) -> TokenStream {
+    let name = variant_name(variant, rename_rule);
+    let helpers = quote!(#crate_path::__private::json_schema);
+    let body_option = match &body {
+        Some(body) => quote!(::core::option::Option::Some(#body)),
+        None => quote!(::core::option::Option::None),
+    };
+    match tagging {
+        Tagging::External => quote!(#helpers::external(#name, #body_option)),
+        Tagging::Internal(tag) => quote!(#helpers::internal(#tag, #name, #body_option)),
+        Tagging::Adjacent(tag, content) => {
+            quote!(#helpers::adjacent(#tag, #content, #name, #body_option))
+        }
+        Tagging::Untagged => body.unwrap_or_else(|| quote!(#helpers::unit())),
+    }
+}
+
+/// The expression building the schema of a single field
+fn field_schema(info: &FieldInfo, crate_path: &Path) -> TokenStream {
+    let ty = info.value_type(crate_path);
+    quote!(<#ty as #crate_path::json_schema::JsonSchema>::schema(generator))
+}
+
+/// The `JsonSchema` implementation and the inherent `json_schema` function of an item, whose
+/// schema is built by `body`.
+///
+/// Fields which depend on generic parameters get a `JsonSchema` bound, similar to the bounds
+/// inferred by serde.
+pub(crate) fn json_schema_impl(
+    ident: &Ident,
+    attrs: &[Attribute],
+    generics: &Generics,
+    fields: &[&FieldInfo],
+    body: TokenStream,
+    crate_path: &Path,
+) -> TokenStream {
+    let type_params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for info in fields
+        .iter()
+        .filter(|info| !info.skipped && !info.flattened)
+    {
+        let ty = &info.ty;
+        if !mentions_any(ty.to_token_stream(), &type_params) {
+            continue;
+        }
+        if info.unwrapped {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: #crate_path::__private::OptionLike));
+        }
+        let schema_type = info.value_type(crate_path);
+        where_clause
+            .predicates
+            .push(parse_quote!(#schema_type: #crate_path::json_schema::JsonSchema));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = serde_attribute_value(attrs, "rename").unwrap_or_else(|| ident.to_string());
+    quote! {
+        impl #impl_generics #crate_path::json_schema::JsonSchema for #ident #ty_generics
+        #where_clause
+        {
+            fn schema(
+                generator: &mut #crate_path::json_schema::SchemaGenerator,
+            ) -> #crate_path::__private::serde_json::Value {
+                generator.named_schema::<Self>(#name, |generator| #body)
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// The JSON Schema (draft 2020-12) of this type, as generated by
+            /// `#[serde_option(json_schema)]`
+            pub fn json_schema() -> #crate_path::__private::serde_json::Value {
+                #crate_path::json_schema::schema_for::<Self>()
+            }
+        }
+    }
+}
@@ -30,12 +30,26 @@
 //! IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 //! DEALINGS IN THE SOFTWARE.
 
-use proc_macro2::Span;
-use quote::quote;
+mod binary_safe;
+mod fields;
+#[cfg(feature = "json_schema")]
+mod json_schema;
+#[cfg(feature = "validate")]
+mod validate;
+
+use fields::{FieldInfo, RenameRule};
+use proc_macro2::{Span, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    meta, parse::Parser, parse_quote, spanned::Spanned, AngleBracketedGenericArguments, Error,
-    Field, Fields, GenericArgument, ItemEnum, ItemStruct, PathArguments, QSelf, Type, TypeGroup,
-    TypeParen, TypePath,
+    ext::IdentExt,
+    meta::{self, ParseNestedMeta},
+    parse::Parser,
+    parse_quote,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token, AngleBracketedGenericArguments, Attribute, Error, Expr, ExprLit, ExprPath, ExprUnary,
+    Field, Fields, GenericArgument, Generics, Ident, ItemEnum, ItemStruct, Lit, LitBool, LitStr,
+    Meta, Path, PathArguments, QSelf, Token, Type, TypeGroup, TypeParen, TypePath, UnOp,
 };
 
 /// Process `#[nullable]` and `#[not_required]` annotations in [`Option`] fields and
@@ -72,33 +86,423 @@ use syn::{
 /// }
 /// ```
 ///
-/// This is equivalent to the following struct definition:
+/// This is equivalent to the following definitions, where the helpers generated for the fields
+/// are associated functions of the item:
 ///
 /// ```
 /// # use serde::Serialize;
-/// # use serde_option_macros::serde_option;
 /// #[derive(Serialize)]
 /// struct Data {
-///     #[serde(with = "Option")]
+///     #[serde(with = "::core::option::Option")]
 ///     nullable_field: Option<String>,
-///     #[serde(default, skip_serializing_if = "Option::is_none")]
-///     #[serde(with = "serde_with::rust::unwrap_or_skip")]
+///     #[serde(
+///         default,
+///         skip_serializing_if = "::core::option::Option::is_none",
+///         serialize_with = "::serde_option::__private::unwrap_or_skip::serialize",
+///     )]
+///     #[serde(deserialize_with = "Data::__serde_option_deserialize_not_required_field")]
 ///     not_required_field: Option<u64>,
-///     #[serde(default, skip_serializing_if = "Option::is_none")]
-///     #[serde(with = "serde_with::rust::double_option")]
+///     #[serde(
+///         default,
+///         skip_serializing_if = "::core::option::Option::is_none",
+///         with = "::serde_option::__private::double_option",
+///     )]
 ///     nullable_and_not_required_field: Option<Option<String>>,
 ///     #[serde(default)]
+///     #[serde(with = "::core::option::Option")]
 ///     nullable_with_default: Option<String>,
 ///     #[serde(skip)]
 ///     skipped_field: Option<bool>,
 /// }
+///
+/// const _: () = {
+///     impl Data {
+///         // Reports `null` as an error naming the field
+///         fn __serde_option_deserialize_not_required_field<'de, D, O>(
+///             deserializer: D,
+///         ) -> Result<O, D::Error>
+///         where
+///             D: serde::Deserializer<'de>,
+///             O: serde_option::__private::OptionLike,
+///             O::Inner: serde::Deserialize<'de>,
+///         {
+///             serde_option::__private::unwrap_or_skip::deserialize_field(
+///                 deserializer,
+///                 "not_required_field",
+///             )
+///         }
+///     }
+///
+///     // ... and the `SerdeOptionFields` implementation describing the fields
+/// };
+/// ```
+///
+/// # Skip predicates
+///
+/// `#[not_required]` fields skip `None` during serialization. Additional values can be
+/// skipped with `skip_if`, which is called with the value inside of `Some`. The field's own
+/// `#[serde(skip_serializing_if = "...")]` can't be combined with the marker.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option]
+/// #[derive(Serialize)]
+/// struct Data {
+///     #[not_required(skip_if = "Vec::is_empty")]
+///     tags: Option<Vec<String>>, // `None` and `Some(vec![])` are skipped
+///     #[not_required(skip_if = "str::is_empty")]
+///     name: Option<String>,
+/// }
+/// ```
+///
+/// # Lenient fields
+///
+/// Some APIs send `null` and leave out fields interchangeably. With
+/// `#[not_required(accept_null)]`, both a missing value and `null` are deserialized as `None`,
+/// while `None` is still skipped during serialization, so `null` is never produced. This
+/// replaces a default `#[nullable]` from `default = both`, and can't be combined with an
+/// explicit one.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option]
+/// #[derive(Deserialize, Serialize)]
+/// struct Data {
+///     #[not_required(accept_null)]
+///     lenient_field: Option<u64>, // `{}` and `{"lenient_field": null}` are both `None`
+/// }
+/// ```
+///
+/// # Sentinel values
+///
+/// Some APIs encode `null` as a special value, such as `""`, `0`, `-1` or `"N/A"`. With
+/// `#[nullable(sentinel = ...)]`, this literal is deserialized as `None`, as well as `null`,
+/// even if it doesn't have the type of the value, e.g. `"N/A"` for a `Vec<String>` or a struct.
+/// Numbers are compared by value, so `-1` also matches `-1.0`, and the other way around. `None`
+/// is serialized as `null`, or as the sentinel if `sentinel_out` is given as well. With
+/// `binary_safe`, which can't tell the sentinel apart from a value, the field is (de)serialized
+/// as a regular `Option`.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option]
+/// #[derive(Deserialize, Serialize)]
+/// struct Data {
+///     #[nullable(sentinel = "N/A")]
+///     count: Option<u64>, // `"N/A"` and `null` are `None`, which is serialized as `null`
+///     #[nullable(sentinel = -1, sentinel_out)]
+///     parent_id: Option<i64>, // `None` is serialized as `-1`
+/// }
+/// ```
+///
+/// # Fields with defaults
+///
+/// `#[not_required]` can also be used on fields which aren't an `Option`, and whose type
+/// implements `Default` and `PartialEq`. Missing values are filled in with the default, and
+/// values equal to the default are skipped during serialization. With `#[not_required(empty)]`,
+/// values are skipped if their `is_empty()` method returns `true` instead, which doesn't need
+/// `PartialEq`, and `skip_if` replaces the comparison with a custom predicate. Unlike for
+/// `Option` fields, `null` is left to the type to handle.
+///
+/// This includes type aliases of `Option` which aren't listed in `option_aliases`, since the
+/// macro can't recognize them: `None` is skipped and filled back in, but `null` is accepted as
+/// `None` instead of being rejected. See [Type aliases](#type-aliases) for the `Option`
+/// semantics.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// # use std::collections::HashMap;
+/// #[serde_option]
+/// #[derive(Serialize)]
+/// struct Data {
+///     #[not_required]
+///     enabled: bool, // `false` is skipped
+///     #[not_required(empty)]
+///     labels: HashMap<String, String>,
+/// }
+/// ```
+///
+/// Likewise, `#[nullable(as_default)]` accepts `null` for such fields, and deserializes it as
+/// the default value. The value is serialized as usual, so `null` is never produced.
+///
+/// ```
+/// # use serde::Deserialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option]
+/// #[derive(Deserialize)]
+/// struct Data {
+///     #[nullable(as_default)]
+///     tags: Vec<String>, // `null` becomes `vec![]`
+/// }
+/// ```
+///
+/// # Default presence
+///
+/// Instead of annotating every field, a default can be set for all `Option` fields of the
+/// struct or enum with `default = not_required`, `default = nullable` or `default = both`.
+/// Fields can opt out of the default with `#[required]` or `#[non_nullable]`, or pick the
+/// opposite marker. Fields with `#[serde(skip)]`, `#[serde(with = "...")]` or similar are left
+/// untouched, and fields with `#[serde(default)]` are not made `#[not_required]`.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(default = not_required)]
+/// #[derive(Serialize)]
+/// struct Data {
+///     not_required_field: Option<u64>,
+///     #[nullable]
+///     nullable_and_not_required_field: Option<Option<String>>,
+///     #[required]
+///     #[nullable]
+///     nullable_field: Option<String>,
+/// }
+/// ```
+///
+/// Note that `default = both` expects the fields to be of type `Option<Option<T>>`, just like
+/// when using both markers by hand.
+///
+/// # Strict mode
+///
+/// With `strict`, every `Option` field has to be marked with `#[nullable]` and/or
+/// `#[not_required]` (or get them from `default = ...`), so that serde's implicit handling
+/// of `Option` is never used by accident. Other fields raise a compile error. Fields with
+/// `#[serde(skip)]`, a custom `#[serde(with = "...")]`, or an explicit `#[required]` or
+/// `#[non_nullable]` marker are allowed. Use `strict = warn` to raise a warning instead.
+///
+/// ```compile_fail
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(strict)]
+/// #[derive(Serialize)]
+/// struct Data {
+///     forgotten_field: Option<u64>, // Error: `Option` fields must be marked with ...
+/// }
+/// ```
+///
+/// # Forms
+///
+/// HTML forms and query strings have no `null`, only empty values. With `form`, an empty value
+/// of a `#[nullable]` field is deserialized as `None`, and `None` is serialized as an empty
+/// value, while a missing key is still an absent field. This also holds for fields with both
+/// markers, for `Patch` fields and for `#[nullable(as_default)]`, so that all three states
+/// round-trip through formats such as `serde_urlencoded`. Values are parsed from their text,
+/// e.g. `count=1` for an `Option<u64>`. Only empty strings are `None`, values other than
+/// strings are deserialized as they are, so that the item still works with e.g. JSON.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(form)]
+/// #[derive(Deserialize, Serialize)]
+/// struct Query {
+///     #[nullable]
+///     name: Option<String>, // `name=` is `None`
+///     #[nullable]
+///     #[not_required]
+///     limit: Option<Option<u64>>, // `limit=` is `Some(None)`, and a missing key is `None`
+/// }
+/// ```
+///
+/// # Binary formats
+///
+/// Formats which aren't self-describing, such as `bincode` or `postcard`, read the fields by
+/// position, so skipped fields make the data unreadable. `binary_safe` declares that the item
+/// is used with such formats: fields are never skipped, `#[not_required]`, `Patch` and sentinel
+/// fields are written with an explicit presence tag, i.e. as an `Option` of everything they
+/// can hold, and `#[nullable(as_default)]` fields are written as their value.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(binary_safe)]
+/// #[derive(Deserialize, Serialize)]
+/// struct Cached {
+///     #[not_required]
+///     etag: Option<String>, // always written as an `Option`
+/// }
+/// ```
+///
+/// This applies to every format the item is used with, e.g. JSON gets `null` instead of a
+/// skipped field, and `#[not_required]` fields accept `null`. JSON also can't tell `Patch::Null`
+/// apart from `Patch::Absent`: both are written as `null`, which is read back as
+/// `Patch::Absent`. Self-describing formats are therefore better served by a separate item
+/// without `binary_safe`. It can't be combined with `form` or `null_repr`. Without
+/// `binary_safe`, the generated code assumes a self-describing format, including
+/// non-human-readable ones such as MessagePack or CBOR.
+///
+/// # Formats without `null`
+///
+/// Some formats, such as TOML, have no `null`, and fail to serialize `None`. With
+/// `null_repr = omit`, `None` values of `#[nullable]` fields are left out, and missing values are
+/// deserialized as `None`. This can't tell `null` apart from a missing value, so fields with
+/// both markers and `Patch` fields need `null_repr = "..."` instead, which writes `null` as the
+/// given string, and reads it back as `null`. Both apply to every format the item is used with,
+/// and `null` itself is still accepted.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(null_repr = "none")]
+/// #[derive(Deserialize, Serialize)]
+/// struct Config {
+///     #[nullable]
+///     proxy: Option<String>, // `None` is written as `proxy = "none"`
+///     #[nullable]
+///     #[not_required]
+///     timeout: Option<Option<u64>>, // `Some(None)` is `timeout = "none"`, `None` is left out
+/// }
+/// ```
+///
+/// # XML
+///
+/// XML Schema marks `null` elements with `xsi:nil="true"`, while optional elements are left out.
+/// With the `xml` feature, `#[serde_option(xml)]` writes `None` of `#[nullable]` fields,
+/// `Some(None)` of fields with both markers and `Patch::Null` as such an element, for use with
+/// `quick-xml`, which reads it back as `None`. `#[not_required]` fields are still left out when
+/// `None`, and reject `xsi:nil="true"`.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(xml)]
+/// #[derive(Deserialize, Serialize)]
+/// struct Order {
+///     #[nullable]
+///     note: Option<String>,
+///     #[not_required]
+///     coupon: Option<String>,
+/// }
+///
+/// let order = Order { note: None, coupon: None };
+/// assert_eq!(
+///     quick_xml::se::to_string(&order).unwrap(),
+///     r#"<Order><note xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:nil="true"/></Order>"#
+/// );
+/// ```
+///
+/// The `xsi` namespace is declared on every nil element, and has to be declared on the element
+/// or one of its parents for `quick-xml` to recognize `xsi:nil` when reading.
+///
+/// `#[nullable(sentinel = ..., sentinel_out)]` writes the sentinel instead of `xsi:nil`. Since
+/// XML elements only hold text, the sentinel is compared with the text of numbers, booleans and
+/// strings, e.g. `<count>-1</count>` for `sentinel = -1`, but not with elements holding other
+/// elements, such as structs.
+///
+/// # Type aliases
+///
+/// Type aliases of [`Option`], and generic parameters which are instantiated with an
+/// [`Option`], can't be detected by the macro. Such fields can be asserted to be an [`Option`]
+/// with the `assume_option` argument on a marker, or by listing the names of the aliases with
+/// `option_aliases(...)` on the container. These fields are then treated as if they were
+/// declared as `Option<T>`, including for `default = ...` and `strict`.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// type Maybe<T> = Option<T>;
+/// type OptionalId = Option<u64>;
+///
+/// #[serde_option(option_aliases(Maybe, OptionalId))]
+/// #[derive(Serialize)]
+/// struct Data<T> {
+///     #[nullable]
+///     alias_field: Maybe<String>,
+///     #[not_required]
+///     id_field: OptionalId,
+///     #[nullable(assume_option)]
+///     generic_field: T,
+/// }
+/// ```
+///
+/// # Patch fields
+///
+/// Fields of type `serde_option::Patch<T>` are always treated as nullable and not required,
+/// so no markers are needed. The macro adds `#[serde(default)]` and skips serializing
+/// `Patch::Absent`.
+///
+/// The type is recognized by its path, since other types may be named `Patch` as well. When
+/// `Patch` is imported, or given another name with a type alias, the name has to be listed with
+/// `patch_aliases(...)` on the container. Other types named `Patch` are left untouched.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// use serde_option::Patch;
+///
+/// #[serde_option(patch_aliases(Patch))]
+/// #[derive(Serialize)]
+/// struct Data {
+///     patch_field: Patch<String>,
+///     other_patch_field: serde_option::Patch<u64>,
+/// }
+/// ```
+///
+/// # Field metadata
+///
+/// The macro also implements `serde_option::SerdeOptionFields` for the item, which lists the
+/// serialized name of every field, and whether it is nullable, required, skipped or has a
+/// default. For enums, the fields are listed per variant.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// use serde_option::SerdeOptionFields;
+///
+/// #[serde_option]
+/// #[derive(Serialize)]
+/// struct Data {
+///     #[nullable]
+///     nullable_field: Option<String>,
+/// }
+///
+/// assert!(Data::FIELDS[0].nullable);
+/// ```
+///
+/// # Errors
+///
+/// `null` values of `#[not_required]` fields, unless given `accept_null`, are rejected with a
+/// message naming the violated rule, e.g. ``field `/not_required_field` must not be null``,
+/// instead of the error of the inner type. Inner types which accept `null` themselves, such as
+/// `serde_json::Value`, are deserialized from it as usual. Missing required fields are still
+/// reported with serde's own message. `Error::parse` recognizes both messages on a best-effort
+/// basis, and turns them into a `serde_option::Error` naming the field. Messages of formats
+/// which word missing fields differently, or of `#[serde(untagged)]` enums, aren't recognized.
+///
+/// # Crate paths
+///
+/// The expansion refers to helpers in the `serde_option` crate as `::serde_option`. If the
+/// crate is renamed or only available through a re-export, its path can be given with
+/// `crate = "..."`. Similarly, `serde = "..."` forwards the path of the `serde` crate to the
+/// container as `#[serde(crate = "...")]`, unless the container already sets it.
+///
+/// ```
+/// mod platform {
+///     pub use serde;
+///     pub use serde_option;
+/// }
+/// use platform::serde::Serialize;
+/// # use serde_option_macros::serde_option;
+///
+/// #[serde_option(crate = "platform::serde_option", serde = "platform::serde")]
+/// #[derive(Serialize)]
+/// struct Data {
+///     #[not_required]
+///     not_required_field: Option<u64>,
+///     patch_field: platform::serde_option::Patch<u64>,
+/// }
 /// ```
 ///
 /// # Features
 ///
 /// When compiling with the `utoipa` feature, this macro can also add
 /// `#[schema(required = true)]` to required + nullable fields, and
-/// `#[schema(schema_with = ...)]` to optional + non-nullable fields.
+/// `#[schema(schema_with = ...)]` to optional + non-nullable fields. Fields which are both
+/// nullable and not required are described as a single optional `Option<T>`.
 ///
 /// ```
 /// # use serde::Serialize;
@@ -114,19 +518,107 @@ use syn::{
 /// }
 /// ```
 ///
+/// The `utoipa` attributes are also enabled when the item derives `ToSchema` or `IntoParams`,
+/// as long as `#[serde_option]` is placed above the `#[derive(...)]`. Use
+/// `#[serde_option(utoipa = false)]` to opt out.
+///
+/// Nullable fields with `#[serde(default)]` are not required, and the value returned by
+/// `#[serde(default = "path")]` is documented as the default of the schema.
+///
+/// For items deriving `IntoParams`, the matching `#[param(required = ..., nullable = ...)]`
+/// attributes are emitted as well.
+///
+/// The schemas follow OpenAPI 3.1, where `null` is part of the `type`. Use
+/// `#[serde_option(utoipa(openapi = "3.0"))]` to mark nullable fields with `nullable: true`
+/// instead. Since this replaces the schema of those fields, the schemas of nested types are
+/// inlined rather than referenced.
+///
+/// Likewise, the `schemars` feature adds `#[serde_option(schemars)]`, which emits
+/// `#[schemars(with = "...")]` attributes, so that the generated JSON Schema agrees with serde:
+/// `#[nullable]` fields are required and accept `null`, `#[not_required]` fields are optional
+/// and don't accept `null`, and fields with both markers are optional and accept `null`.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// # use schemars::JsonSchema;
+/// #[serde_option(schemars)]
+/// #[derive(Serialize, JsonSchema)]
+/// struct Data {
+///     #[nullable]
+///     nullable_field: Option<String>,
+///     #[not_required]
+///     not_required_field: Option<String>,
+/// }
+/// ```
+///
+/// Generic parameters opted in with `#[not_required(assume_option)]` are not supported by
+/// `#[derive(JsonSchema)]`, type aliases given in `option_aliases` are.
+///
+/// Without a schema library, the `json_schema` feature adds `#[serde_option(json_schema)]`,
+/// which implements `serde_option::json_schema::JsonSchema` and an inherent
+/// `fn json_schema() -> serde_json::Value` returning a draft 2020-12 schema. The field types
+/// have to implement the trait as well, e.g. by using the macro on nested types.
+///
+/// ```
+/// # use serde::Serialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(json_schema)]
+/// #[derive(Serialize)]
+/// struct Data {
+///     #[nullable]
+///     nullable_field: Option<String>,
+///     #[not_required]
+///     not_required_field: Option<String>,
+/// }
+///
+/// let schema = Data::json_schema();
+/// assert_eq!(schema["required"], serde_json::json!(["nullable_field"]));
+/// ```
+///
+/// The `validate` feature adds `#[serde_option(validate)]`, which implements
+/// `serde_option::validate::ValidatePresence` and an inherent
+/// `fn validate_presence(&serde_json::Value)`. It reports all missing required fields and
+/// unexpected `null` values at once, with JSON pointers to the offending fields. Nested types
+/// using `validate` are validated as well.
+///
+/// ```
+/// # use serde::Deserialize;
+/// # use serde_option_macros::serde_option;
+/// #[serde_option(validate)]
+/// #[derive(Deserialize)]
+/// struct Data {
+///     #[nullable]
+///     nullable_field: Option<String>,
+///     #[not_required]
+///     not_required_field: Option<String>,
+/// }
+///
+/// let errors = Data::validate_presence(&serde_json::json!({"not_required_field": null}));
+/// assert_eq!(errors.unwrap_err().len(), 2);
+/// ```
+///
+/// The `serde_option::__private` helpers behave like `serde_with::rust::unwrap_or_skip` and
+/// `serde_with::rust::double_option` from the [`serde_with`] crate. They live in the
+/// `serde_option` crate so that no other dependency is needed for the expansion to work.
+///
 /// # Limitations
 ///
-/// You must have the [`serde_with`] crate installed for the expansion to work.
+/// You must have the `serde_option` crate installed for the expansion to work, since the
+/// generated attributes refer to helpers inside of it.
 ///
 /// Certain combinations of attributes are invalid and will raise a compile error:
 /// * Using either `#[nullable]` or `#[not_required]` together with `#[serde(skip)]`
 /// * Using `#[serde(default)]` with `#[not_required]`
+/// * Using a marker together with its opposite, e.g. `#[required]` with `#[not_required]`
 ///
 /// The [`macro@serde_option`] only works if the type is called `Option`,
-/// `std::option::Option`, or `core::option::Option`. Type aliasing an [`Option`] and giving it
-/// another name, will cause a compile error. This cannot be supported, as proc-macros run
-/// before type checking, thus it is not possible to determine if a type alias refers to an
-/// [`Option`].
+/// `std::option::Option`, or `core::option::Option`. This cannot be detected automatically, as
+/// proc-macros run before type checking, thus it is not possible to determine if a type alias
+/// refers to an [`Option`]. `#[nullable]` on a type alias of [`Option`] will cause a compile
+/// error, while `#[not_required]` falls back to the semantics of
+/// [fields with defaults](#fields-with-defaults). See [Type aliases](#type-aliases) for how to
+/// opt in explicitly.
 ///
 /// ```compile_fail
 /// # use serde::Serialize;
@@ -164,118 +656,1215 @@ pub fn serde_option(
     attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let mut utoipa_flag = false;
-    let attr_parsed = meta::parser(|meta| {
-        if meta.path.is_ident("utoipa") {
-            utoipa_flag = true;
-            Ok(())
-        } else {
-            Err(meta.error("Unsupported property in macro input"))
-        }
-    })
-    .parse(attr);
+    let mut options = ContainerOptions::default();
+    let attr_parsed = meta::parser(|meta| options.parse(meta))
+        .parse(attr)
+        .and_then(|_| options.check());
     let res = attr_parsed
-        .and_then(|_| process_items(item, utoipa_flag))
+        .and_then(|_| process_items(item, &mut options))
         .unwrap_or_else(|err| err.to_compile_error());
     proc_macro::TokenStream::from(res)
 }
 
-/// Applies the `#[nullable]` and `#[not_required]` transformations on a field. This will only
-/// work for fields whose type is statically assumed to be `Option<T>`
-fn process_optional_field(
-    field: &mut Field,
-    #[allow(unused_variables)] utoipa_flag: bool,
-) -> Result<(), String> {
-    // Detect and remove `#[nullable]` and `#[not_required]` attributes from the attribute list
-    let mut nullable = false;
-    let mut not_required = false;
-    field.attrs.retain(|attr| {
-        if attr.path().is_ident("nullable") {
-            nullable = true;
-            false
-        } else if attr.path().is_ident("not_required") {
-            not_required = true;
-            false
+/// Properties given to the macro itself, e.g. `#[serde_option(utoipa)]`
+struct ContainerOptions {
+    /// Emit `#[schema(...)]` attributes for `utoipa`. `None` means that this is detected from
+    /// the derives on the item
+    utoipa: Option<bool>,
+    /// The OpenAPI version targeted by the `utoipa` attributes
+    openapi: OpenApiVersion,
+    /// Emit `#[schema(...)]` attributes, for items deriving `ToSchema`
+    to_schema: bool,
+    /// Emit `#[param(...)]` attributes, for items deriving `IntoParams`
+    into_params: bool,
+    /// Emit `#[schemars(...)]` attributes for `schemars`
+    schemars: bool,
+    /// Implement `serde_option::json_schema::JsonSchema` for the item
+    json_schema: bool,
+    /// Implement `serde_option::validate::ValidatePresence` for the item
+    validate: bool,
+    /// Use form semantics, where an empty value is `null`, as given with `form`
+    form: bool,
+    /// Never skip fields, and tag the presence of values, for formats which aren't
+    /// self-describing, as given with `binary_safe`
+    binary_safe: bool,
+    /// How `null` is written for formats without it, as given with `null_repr = ...`
+    null_repr: Option<NullRepr>,
+    /// Write `None` as an element with `xsi:nil="true"`, as given with `xml`
+    xml: bool,
+    /// Path to the `serde_option` crate, used to refer to the helpers in the expansion
+    crate_path: Path,
+    /// Path to the `serde` crate, forwarded to the container as `#[serde(crate = "...")]`
+    serde_path: Option<Path>,
+    /// Presence rules for `Option` fields which are not annotated with markers
+    default: Presence,
+    /// How to report `Option` fields without markers, as given with `strict`
+    strict: Option<Strictness>,
+    /// Names of type aliases which should be treated as `Option`
+    option_aliases: Vec<Ident>,
+    /// Names under which `serde_option::Patch` is imported or aliased
+    patch_aliases: Vec<Ident>,
+    /// Name of the item, which is filled in when processing the item
+    ident: Ident,
+    /// Generic parameters of the item, which are filled in when processing the item
+    generics: Generics,
+}
+
+/// The severity used by `#[serde_option(strict)]`
+#[derive(Clone, Copy)]
+enum Strictness {
+    Deny,
+    Warn,
+}
+
+impl Strictness {
+    /// Parse the optional value of `strict = ...`
+    fn parse(meta: &ParseNestedMeta) -> Result<Self, Error> {
+        if !meta.input.peek(Token![=]) {
+            return Ok(Strictness::Deny);
+        }
+        let ident: Ident = meta.value()?.parse()?;
+        if ident == "deny" {
+            Ok(Strictness::Deny)
+        } else if ident == "warn" {
+            Ok(Strictness::Warn)
         } else {
-            true
+            Err(Error::new(ident.span(), "Expected one of `deny` or `warn`"))
         }
-    });
-    // `inner_type` is unused when the `"utoipa"` feature is disabled
-    #[allow(unused_variables)]
-    if let Some(inner_type) = get_std_option(&field.ty) {
-        // Detect `#[serde(skip)]` and `#[serde(default)]` attributes
-        let skipped = field_has_attribute(field, "serde", "skip");
-        let default = field_has_attribute(field, "serde", "default");
+    }
+}
 
-        // The attributes are invalid and make no sense when combined with `#[serde(skip)]`
-        if skipped && nullable {
-            return Err("`#[nullable]` cannot be used in combination with `#[serde(skip)]`".into());
-        } else if skipped && not_required {
-            return Err(
-                "`#[not_required]` cannot be used in combination with `#[serde(skip)]`".into(),
-            );
-        } else if default && not_required {
-            return Err(
-                "`#[not_required]` cannot be used in combination with `#[serde(default)]`".into(),
-            );
-        // Emit the appropriate serde attributes in the following cases
-        } else if !nullable && not_required {
-            field.attrs.push(parse_quote! {
-                #[serde(default, skip_serializing_if = "Option::is_none",
-                    with = "serde_with::rust::unwrap_or_skip")]
-            });
-            #[cfg(feature = "utoipa")]
-            if utoipa_flag {
+/// The OpenAPI version used by `#[serde_option(utoipa(openapi = "..."))]`
+#[derive(Clone, Copy, Default, PartialEq)]
+enum OpenApiVersion {
+    /// Nullable fields are described with `nullable: true`
+    V3_0,
+    /// Nullable fields are described with `null` in the `type` array
+    #[default]
+    V3_1,
+}
+
+impl OpenApiVersion {
+    /// Parse the value of `openapi = "..."`
+    fn parse(meta: &ParseNestedMeta) -> Result<Self, Error> {
+        let version: LitStr = meta.value()?.parse()?;
+        match version.value().as_str() {
+            "3.0" => Ok(OpenApiVersion::V3_0),
+            "3.1" => Ok(OpenApiVersion::V3_1),
+            _ => Err(Error::new(
+                version.span(),
+                "Expected one of `\"3.0\"` or `\"3.1\"`",
+            )),
+        }
+    }
+}
+
+/// How `null` is written for formats without it, such as TOML, as given with
+/// `#[serde_option(null_repr = ...)]`
+enum NullRepr {
+    /// `null` is left out, like a missing value
+    Omit,
+    /// `null` is written as the string, which is read as `null` as well
+    Str(LitStr),
+}
+
+impl NullRepr {
+    /// Parse the value of `null_repr = ...`
+    fn parse(meta: &ParseNestedMeta) -> Result<Self, Error> {
+        let value = meta.value()?;
+        if value.peek(LitStr) {
+            return Ok(NullRepr::Str(value.parse()?));
+        }
+        let ident: Ident = value.parse()?;
+        if ident == "omit" {
+            Ok(NullRepr::Omit)
+        } else {
+            Err(Error::new(
+                ident.span(),
+                "Expected `omit` or a string literal",
+            ))
+        }
+    }
+}
+
+impl Default for ContainerOptions {
+    fn default() -> Self {
+        Self {
+            utoipa: None,
+            openapi: OpenApiVersion::default(),
+            to_schema: false,
+            into_params: false,
+            schemars: false,
+            json_schema: false,
+            validate: false,
+            form: false,
+            binary_safe: false,
+            null_repr: None,
+            xml: false,
+            crate_path: parse_quote!(::serde_option),
+            serde_path: None,
+            default: Presence::default(),
+            strict: None,
+            option_aliases: Vec::new(),
+            patch_aliases: Vec::new(),
+            ident: Ident::new("Self", Span::call_site()),
+            generics: Generics::default(),
+        }
+    }
+}
+
+/// Which presence rules apply to a field, e.g. as given with `#[serde_option(default = ...)]`
+#[derive(Clone, Copy, Default)]
+struct Presence {
+    nullable: bool,
+    not_required: bool,
+}
+
+impl Presence {
+    /// Parse the value of `default = ...`
+    fn parse(meta: &ParseNestedMeta) -> Result<Self, Error> {
+        let ident: Ident = meta.value()?.parse()?;
+        let (nullable, not_required) = if ident == "nullable" {
+            (true, false)
+        } else if ident == "not_required" {
+            (false, true)
+        } else if ident == "both" {
+            (true, true)
+        } else {
+            return Err(Error::new(
+                ident.span(),
+                "Expected one of `nullable`, `not_required` or `both`",
+            ));
+        };
+        Ok(Self {
+            nullable,
+            not_required,
+        })
+    }
+}
+
+impl ContainerOptions {
+    /// Parse a single property of the macro input
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<(), Error> {
+        if meta.path.is_ident("utoipa") {
+            self.utoipa = Some(if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<LitBool>()?.value
+            } else {
+                if meta.input.peek(token::Paren) {
+                    meta.parse_nested_meta(|nested| {
+                        if nested.path.is_ident("openapi") {
+                            self.openapi = OpenApiVersion::parse(&nested)?;
+                            Ok(())
+                        } else {
+                            Err(nested.error("Unsupported property in `utoipa(...)`"))
+                        }
+                    })?;
+                }
+                true
+            });
+        } else if meta.path.is_ident("schemars") {
+            self.schemars = true;
+        } else if meta.path.is_ident("json_schema") {
+            self.json_schema = true;
+        } else if meta.path.is_ident("validate") {
+            self.validate = true;
+        } else if meta.path.is_ident("form") {
+            self.form = true;
+        } else if meta.path.is_ident("binary_safe") {
+            self.binary_safe = true;
+        } else if meta.path.is_ident("null_repr") {
+            self.null_repr = Some(NullRepr::parse(&meta)?);
+        } else if meta.path.is_ident("xml") {
+            self.xml = true;
+        } else if meta.path.is_ident("crate") {
+            self.crate_path = meta.value()?.parse::<LitStr>()?.parse()?;
+        } else if meta.path.is_ident("serde") {
+            self.serde_path = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+        } else if meta.path.is_ident("default") {
+            self.default = Presence::parse(&meta)?;
+        } else if meta.path.is_ident("strict") {
+            self.strict = Some(Strictness::parse(&meta)?);
+        } else if meta.path.is_ident("option_aliases") {
+            meta.parse_nested_meta(|alias| {
+                self.option_aliases
+                    .push(alias.path.require_ident()?.clone());
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("patch_aliases") {
+            meta.parse_nested_meta(|alias| {
+                self.patch_aliases.push(alias.path.require_ident()?.clone());
+                Ok(())
+            })?;
+        } else {
+            return Err(meta.error("Unsupported property in macro input"));
+        }
+        Ok(())
+    }
+
+    /// Reject properties which contradict each other
+    fn check(&self) -> Result<(), Error> {
+        let message = if self.binary_safe && (self.form || self.null_repr.is_some()) {
+            "`binary_safe` cannot be used in combination with `form` or `null_repr`"
+        } else if self.null_repr.is_some() && self.form {
+            "`null_repr` cannot be used in combination with `form`, where empty values are `null`"
+        } else if self.xml && (self.form || self.binary_safe || self.null_repr.is_some()) {
+            "`xml` cannot be used in combination with `form`, `binary_safe` or `null_repr`"
+        } else if self.xml && !cfg!(feature = "xml") {
+            "`xml` requires the `xml` feature of `serde_option`"
+        } else if self.validate && !cfg!(feature = "validate") {
+            "`validate` requires the `validate` feature of `serde_option`"
+        } else if self.schemars && !cfg!(feature = "schemars") {
+            "`schemars` requires the `schemars` feature of `serde_option`"
+        } else if self.json_schema && !cfg!(feature = "json_schema") {
+            "`json_schema` requires the `json_schema` feature of `serde_option`"
+        } else {
+            return Ok(());
+        };
+        Err(Error::new(Span::call_site(), message))
+    }
+
+    /// A string literal referring to `path` inside of the `serde_option` crate, for use in
+    /// `#[serde(...)]` attributes
+    fn crate_item(&self, path: &str) -> LitStr {
+        let crate_path = &self.crate_path;
+        let crate_path = quote!(#crate_path).to_string().replace(' ', "");
+        LitStr::new(&format!("{crate_path}::{path}"), Span::call_site())
+    }
+}
+
+/// The presence markers given on a single field. `Some(false)` means the field is explicitly
+/// opted out with `#[non_nullable]` or `#[required]`, `None` means no marker was given.
+#[derive(Default)]
+struct FieldMarkers {
+    nullable: Option<bool>,
+    not_required: Option<bool>,
+    /// Treat the field as `Option<T>`, even if the type isn't recognized as such
+    assume_option: bool,
+    /// Additional predicate for skipping `Some` values during serialization, as given with
+    /// `#[not_required(skip_if = "...")]`
+    skip_if: Option<ExprPath>,
+    /// Skip empty values of non-`Option` fields instead of default values, as given with
+    /// `#[not_required(empty)]`
+    empty: bool,
+    /// Deserialize `null` as the default value of non-`Option` fields, as given with
+    /// `#[nullable(as_default)]`
+    as_default: bool,
+    /// Deserialize `null` as `None`, like a missing value, as given with
+    /// `#[not_required(accept_null)]`
+    accept_null: bool,
+    /// The variant of `Sentinel` deserialized as `None`, e.g. `Str("N/A")` for
+    /// `#[nullable(sentinel = "N/A")]`
+    sentinel: Option<proc_macro2::TokenStream>,
+    /// Serialize `None` as the sentinel instead of `null`, as given with
+    /// `#[nullable(sentinel = ..., sentinel_out)]`
+    sentinel_out: bool,
+}
+
+impl FieldMarkers {
+    /// Detect and remove the marker attributes from the attribute list
+    fn take(attrs: &mut Vec<Attribute>) -> Result<Self, String> {
+        let mut markers = Self::default();
+        let mut result = Ok(());
+        attrs.retain(|attr| {
+            let (nullable_marker, value, marker, opposite) = if attr.path().is_ident("nullable") {
+                (true, true, "nullable", "non_nullable")
+            } else if attr.path().is_ident("non_nullable") {
+                (true, false, "non_nullable", "nullable")
+            } else if attr.path().is_ident("not_required") {
+                (false, true, "not_required", "required")
+            } else if attr.path().is_ident("required") {
+                (false, false, "required", "not_required")
+            } else {
+                return true;
+            };
+            let slot = if nullable_marker {
+                &mut markers.nullable
+            } else {
+                &mut markers.not_required
+            };
+            if *slot == Some(!value) {
+                result = Err(format!(
+                    "`#[{marker}]` cannot be used in combination with `#[{opposite}]`"
+                ));
+            }
+            *slot = Some(value);
+            // Markers may have arguments, e.g. `#[nullable(assume_option)]`
+            if let Meta::List(_) = attr.meta {
+                if let Err(err) =
+                    attr.parse_nested_meta(|meta| markers.parse_argument(marker, &meta))
+                {
+                    result = Err(err.to_string());
+                }
+            }
+            false
+        });
+        if markers.sentinel_out && markers.sentinel.is_none() {
+            result = Err("`sentinel_out` requires a `sentinel = ...` value".into());
+        }
+        result.map(|_| markers)
+    }
+
+    /// Parse a single argument of a marker
+    fn parse_argument(&mut self, marker: &str, meta: &ParseNestedMeta) -> Result<(), Error> {
+        if meta.path.is_ident("assume_option") {
+            self.assume_option = true;
+            Ok(())
+        } else if meta.path.is_ident("skip_if") && marker == "not_required" {
+            self.skip_if = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("empty") && marker == "not_required" {
+            self.empty = true;
+            Ok(())
+        } else if meta.path.is_ident("as_default") && marker == "nullable" {
+            self.as_default = true;
+            Ok(())
+        } else if meta.path.is_ident("accept_null") && marker == "not_required" {
+            self.accept_null = true;
+            Ok(())
+        } else if meta.path.is_ident("sentinel") && marker == "nullable" {
+            self.sentinel = Some(parse_sentinel(meta)?);
+            Ok(())
+        } else if meta.path.is_ident("sentinel_out") && marker == "nullable" {
+            self.sentinel_out = true;
+            Ok(())
+        } else {
+            Err(meta.error(format!("Unsupported property in `#[{marker}]`")))
+        }
+    }
+}
+
+/// Parse the literal of `sentinel = ...` into the matching variant of `Sentinel`, e.g.
+/// `Int(-1)` for `sentinel = -1`
+fn parse_sentinel(meta: &ParseNestedMeta) -> Result<proc_macro2::TokenStream, Error> {
+    let expr: Expr = meta.value()?.parse()?;
+    let (sign, lit) = match &expr {
+        Expr::Lit(ExprLit { lit, .. }) => (None, lit),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(minus),
+            expr: inner,
+            ..
+        }) => match &**inner {
+            Expr::Lit(ExprLit { lit, .. }) => (Some(minus), lit),
+            _ => return Err(Error::new(expr.span(), SENTINEL_MESSAGE)),
+        },
+        _ => return Err(Error::new(expr.span(), SENTINEL_MESSAGE)),
+    };
+    match lit {
+        Lit::Str(lit) if sign.is_none() => Ok(quote!(Str(#lit))),
+        Lit::Bool(lit) if sign.is_none() => Ok(quote!(Bool(#lit))),
+        // The suffix is dropped, since the sentinel is compared with values of any type
+        Lit::Int(lit) => {
+            let value = proc_macro2::Literal::u128_unsuffixed(lit.base10_parse()?);
+            Ok(quote!(Int(#sign #value)))
+        }
+        Lit::Float(lit) => {
+            let value = proc_macro2::Literal::f64_unsuffixed(lit.base10_parse()?);
+            Ok(quote!(Float(#sign #value)))
+        }
+        _ => Err(Error::new(expr.span(), SENTINEL_MESSAGE)),
+    }
+}
+
+/// The message shown for unsupported values of `sentinel = ...`
+const SENTINEL_MESSAGE: &str = "Expected a string, integer, float or boolean literal";
+
+/// The error shown for fields which `null_repr = omit` can't represent, since the `values`
+/// would be confused
+fn omit_error(values: &str) -> String {
+    format!(
+        "`null_repr = omit` cannot tell apart {values}
+help: use `null_repr = \"...\"` to write `null` as a string instead"
+    )
+}
+
+/// The message shown by `#[serde_option(strict)]` for `Option` fields without markers
+const STRICT_MESSAGE: &str = "`Option` fields must be marked with `#[nullable]` and/or \
+`#[not_required]` in strict mode
+help: add `#[nullable]`, `#[not_required]` or both, or skip the field with `#[serde(skip)]`";
+
+/// Applies the `#[nullable]` and `#[not_required]` transformations on a field. This will only
+/// work for fields whose type is statically assumed to be `Option<T>` or `Patch<T>`, except for
+/// `#[not_required]`, which falls back to the `Default` of other types
+///
+/// Items which have to be emitted next to the container, such as warnings, are pushed to
+/// `extra_items`. Helper functions generated for the field are named after `helper_prefix`.
+///
+/// Returns the presence rules of the field after the transformation, where `nullable` means
+/// that the field accepts `null`.
+fn process_optional_field(
+    field: &mut Field,
+    helper_prefix: &str,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> Result<Presence, String> {
+    // Detect and remove the marker attributes from the attribute list
+    let markers = FieldMarkers::take(&mut field.attrs)?;
+    let nullable = markers.nullable == Some(true);
+    let not_required = markers.not_required == Some(true);
+    // `inner_type` is unused when the `"utoipa"` and `"schemars"` features are disabled
+    #[allow(unused_variables)]
+    let presence = if let Some(inner_type) = get_patch(&field.ty, options) {
+        // `Patch<T>` is always nullable and not required, so the markers are optional
+        if markers.nullable == Some(false) {
+            return Err("`#[non_nullable]` cannot be used on fields of type `Patch<T>`".into());
+        } else if markers.not_required == Some(false) {
+            return Err("`#[required]` cannot be used on fields of type `Patch<T>`".into());
+        } else if markers.skip_if.is_some() {
+            return Err(
+                "`#[not_required(skip_if = \"...\")]` cannot be used on fields of type `Patch<T>`"
+                    .into(),
+            );
+        } else if markers.empty {
+            return Err(
+                "`#[not_required(empty)]` cannot be used on fields of type `Patch<T>`".into(),
+            );
+        } else if markers.as_default {
+            return Err(
+                "`#[nullable(as_default)]` cannot be used on fields of type `Patch<T>`".into(),
+            );
+        } else if markers.accept_null {
+            return Err(
+                "`#[not_required(accept_null)]` cannot be used on fields of type `Patch<T>`".into(),
+            );
+        } else if markers.sentinel.is_some() {
+            return Err(
+                "`#[nullable(sentinel = ...)]` cannot be used on fields of type `Patch<T>`".into(),
+            );
+        }
+        let skipped = field_has_attribute(field, "serde", "skip");
+        let default = field_has_attribute(field, "serde", "default");
+        let is_absent = options.crate_item("Patch::is_absent");
+
+        if skipped && nullable {
+            return Err("`#[nullable]` cannot be used in combination with `#[serde(skip)]`".into());
+        } else if skipped && not_required {
+            return Err(
+                "`#[not_required]` cannot be used in combination with `#[serde(skip)]`".into(),
+            );
+        } else if skipped {
+            return Ok(Presence::default());
+        } else if default {
+            // A custom default is allowed here, since it does not change how `Patch::Absent`
+            // is skipped during serialization
+            field.attrs.push(parse_quote! {
+                #[serde(skip_serializing_if = #is_absent)]
+            });
+        } else {
+            field.attrs.push(parse_quote! {
+                #[serde(default, skip_serializing_if = #is_absent)]
+            });
+        }
+        let customized = field_has_attribute(field, "serde", "with")
+            || field_has_attribute(field, "serde", "deserialize_with")
+            || field_has_attribute(field, "serde", "serialize_with");
+        // Custom functions decide how `Patch::Null` is written themselves
+        match &options.null_repr {
+            _ if customized => {}
+            Some(NullRepr::Str(repr)) => field.attrs.push(sentinel_helpers(
+                helper_prefix,
+                &quote!(Str(#repr)),
+                true,
+                SentinelField::Patch,
+                options,
+                extra_items,
+            )),
+            Some(NullRepr::Omit) => return Err(omit_error("`Patch::Null` and `Patch::Absent`")),
+            None if options.form || options.binary_safe || options.xml => {
+                let with = if options.form {
+                    options.crate_item("__private::form::patch")
+                } else if options.xml {
+                    options.crate_item("__private::xml::patch")
+                } else {
+                    options.crate_item("__private::binary_safe::patch")
+                };
                 field.attrs.push(parse_quote! {
-                    #[schema(nullable = false)]
+                    #[serde(with = #with)]
+                });
+            }
+            None => {}
+        }
+        #[cfg(feature = "utoipa")]
+        if options.utoipa == Some(true) {
+            if options.to_schema {
+                field.attrs.push(match options.openapi {
+                    OpenApiVersion::V3_0 => utoipa_nullable(&inner_type, false, options),
+                    OpenApiVersion::V3_1 => parse_quote! {
+                        #[schema(value_type = Option<#inner_type>, required = false)]
+                    },
                 })
             }
-        } else if nullable && !not_required {
+            if options.into_params {
+                field.attrs.push(match options.openapi {
+                    OpenApiVersion::V3_0 => utoipa_param(&inner_type, true, false, options),
+                    OpenApiVersion::V3_1 => parse_quote! {
+                        #[param(value_type = Option<#inner_type>, required = false, nullable = true)]
+                    },
+                })
+            }
+        }
+        #[cfg(feature = "schemars")]
+        if options.schemars {
+            schemars_with(field, parse_quote!(::core::option::Option<#inner_type>));
+        }
+        Presence {
+            nullable: true,
+            not_required: true,
+        }
+    } else if let Some(inner_type) = get_std_option(&field.ty).map(Some).or_else(|| {
+        // Types which are only asserted to be `Option`s have an unknown inner type
+        (markers.assume_option || is_option_alias(&field.ty, &options.option_aliases))
+            .then_some(None)
+    }) {
+        // Detect `#[serde(skip)]` and `#[serde(default)]` attributes
+        let skipped = field_has_attribute(field, "serde", "skip");
+        let default = field_has_attribute(field, "serde", "default");
+
+        // The container defaults only apply to fields whose (de)serialization isn't already
+        // customized, and never conflict with the field's own attributes
+        let customized = skipped
+            || field_has_attribute(field, "serde", "with")
+            || field_has_attribute(field, "serde", "deserialize_with")
+            || field_has_attribute(field, "serde", "serialize_with");
+        // `accept_null` takes the place of a default `#[nullable]`
+        let nullable = markers
+            .nullable
+            .unwrap_or(options.default.nullable && !customized && !markers.accept_null);
+        let not_required = markers
+            .not_required
+            .unwrap_or(options.default.not_required && !customized && !default);
+
+        // In strict mode, every field has to make an explicit choice
+        let decided = nullable
+            || not_required
+            || customized
+            || markers.nullable.is_some()
+            || markers.not_required.is_some();
+        match options.strict {
+            Some(Strictness::Deny) if !decided => return Err(STRICT_MESSAGE.into()),
+            Some(Strictness::Warn) if !decided => extra_items.push(strict_warning(field)),
+            _ => {}
+        }
+
+        // The attributes are invalid and make no sense when combined with `#[serde(skip)]`
+        if skipped && nullable {
+            return Err("`#[nullable]` cannot be used in combination with `#[serde(skip)]`".into());
+        } else if skipped && not_required {
+            return Err(
+                "`#[not_required]` cannot be used in combination with `#[serde(skip)]`".into(),
+            );
+        } else if default && not_required {
+            return Err(
+                "`#[not_required]` cannot be used in combination with `#[serde(default)]`".into(),
+            );
+        }
+        if markers.empty {
+            return Err(
+                "`#[not_required(empty)]` cannot be used on fields of type `Option<T>`
+help: use `#[not_required(skip_if = \"...\")]` to skip empty values inside of `Some`"
+                    .into(),
+            );
+        } else if markers.as_default {
+            return Err(
+                "`#[nullable(as_default)]` cannot be used on fields of type `Option<T>`".into(),
+            );
+        }
+        if markers.accept_null && nullable {
+            return Err(
+                "`#[not_required(accept_null)]` cannot be used in combination with `#[nullable]`
+help: `accept_null` already accepts `null`, remove it to tell `null` apart from a missing \
+                value with `Option<Option<T>>`"
+                    .into(),
+            );
+        } else if markers.accept_null
+            && (field_has_attribute(field, "serde", "with")
+                || field_has_attribute(field, "serde", "deserialize_with"))
+        {
+            return Err(
+                "`#[not_required(accept_null)]` cannot be used in combination with \
+                `#[serde(with)]` or `#[serde(deserialize_with)]`"
+                    .into(),
+            );
+        }
+        // Both markers tell a missing value apart from `null` with an `Option` inside of the
+        // `Option`, while a plain `Option<T>` could only reject `null` when deserializing it
+        let single_option = inner_type.as_ref().is_some_and(|inner| {
+            get_std_option(inner).is_none() && !is_option_alias(inner, &options.option_aliases)
+        });
+        if nullable && not_required && !customized && single_option {
+            return Err(
+                "`#[nullable]` and `#[not_required]` together require a field of type \
+                `Option<Option<T>>` or `Patch<T>`
+help: use `#[not_required(accept_null)]` to deserialize both a missing value and `null` as `None`"
+                    .into(),
+            );
+        }
+        if markers.sentinel.is_some() {
+            if options.form {
+                return Err(
+                    "`#[nullable(sentinel = ...)]` cannot be used in combination with \
+                    `#[serde_option(form)]`, where empty values are `None`"
+                        .into(),
+                );
+            } else if not_required {
+                return Err(
+                    "`#[nullable(sentinel = ...)]` cannot be used in combination with \
+                    `#[not_required]`"
+                        .into(),
+                );
+            } else if options.null_repr.is_some() && !markers.sentinel_out {
+                return Err(
+                    "`#[nullable(sentinel = ...)]` requires `sentinel_out` in combination with \
+                    `#[serde_option(null_repr = ...)]`, so that `None` isn't written as `null`"
+                        .into(),
+                );
+            } else if options.xml && !markers.sentinel_out {
+                return Err(
+                    "`#[nullable(sentinel = ...)]` requires `sentinel_out` in combination with \
+                    `#[serde_option(xml)]`, so that `None` isn't written as an empty element"
+                        .into(),
+                );
+            } else if customized {
+                return Err(
+                    "`#[nullable(sentinel = ...)]` cannot be used in combination with \
+                    `#[serde(with)]`, `#[serde(deserialize_with)]` or `#[serde(serialize_with)]`"
+                        .into(),
+                );
+            }
+        }
+        if markers.skip_if.is_some() {
+            if field_has_attribute(field, "serde", "skip_serializing_if") {
+                return Err(
+                    "`#[not_required(skip_if = \"...\")]` cannot be used in combination \
+                    with `#[serde(skip_serializing_if)]`"
+                        .into(),
+                );
+            } else if nullable {
+                // The value inside of `Some` is itself nullable, which makes the predicate
+                // ambiguous
+                return Err(
+                    "`#[not_required(skip_if = \"...\")]` cannot be used in combination with \
+                    `#[nullable]`"
+                        .into(),
+                );
+            }
+        }
+
+        // Fields which are only assumed to be an `Option` need helpers that are generic over
+        // the field type, and matching bounds if the type depends on generic parameters, since
+        // serde can't infer them. Bounds on concrete types are left out, as they can break type
+        // inference in the code generated for other fields.
+        let assumed = inner_type.is_none();
+        let is_none = if assumed {
+            options.crate_item("__private::is_none")
+        } else {
+            parse_quote!("::core::option::Option::is_none")
+        };
+        if assumed
+            && (nullable || not_required)
+            && mentions_any(field.ty.to_token_stream(), &type_params(&options.generics))
+        {
+            let double = nullable && not_required;
+            field
+                .attrs
+                .push(option_like_bounds(&field.ty, double, options));
+        }
+        // The `T` in `Option<T>`, as described in the generated schemas
+        #[cfg(any(feature = "utoipa", feature = "schemars"))]
+        let schema_inner_type: Type = inner_type.clone().unwrap_or_else(|| {
+            let ty = &field.ty;
+            let crate_path = &options.crate_path;
+            parse_quote!(<#ty as #crate_path::__private::OptionLike>::Inner)
+        });
+
+        // Emit the appropriate serde attributes in the following cases
+        if !nullable && not_required {
+            // `deserialize_with` is added by `process_fields`, since the helper reporting
+            // `null` values needs the serialized name of the field, unless `null` is accepted
+            // or the field is written as a regular `Option` for binary formats
+            if markers.accept_null || options.binary_safe {
+                let deserialize_with = if options.form {
+                    options.crate_item("__private::form::deserialize")
+                } else if options.binary_safe {
+                    options.crate_item("__private::nullable::deserialize")
+                } else {
+                    options.crate_item("__private::unwrap_or_skip::deserialize_or_null")
+                };
+                field.attrs.push(parse_quote! {
+                    #[serde(deserialize_with = #deserialize_with)]
+                });
+            }
+            let serialize_with = if options.binary_safe {
+                options.crate_item("__private::nullable::serialize")
+            } else {
+                options.crate_item("__private::unwrap_or_skip::serialize")
+            };
+            // A custom predicate is combined with the `None` check in a generated helper, which
+            // isn't needed for binary formats, where nothing is skipped
+            let skip_serializing_if = match &markers.skip_if {
+                Some(skip_if) if !options.binary_safe => {
+                    let crate_path = &options.crate_path;
+                    let condition = quote! {
+                        #crate_path::__private::OptionLike::as_option(value)
+                            .map_or(true, |value| #skip_if(value))
+                    };
+                    let helper = format_ident!("__serde_option_skip_{helper_prefix}");
+                    skip_predicate(&helper, &field.ty, condition, options, extra_items)
+                }
+                _ => is_none,
+            };
             field.attrs.push(parse_quote! {
-                #[serde(with = "Option")]
+                #[serde(
+                    default,
+                    skip_serializing_if = #skip_serializing_if,
+                    serialize_with = #serialize_with,
+                )]
             });
+            // Fields accepting `null` are described as an optional `Option<T>`, as is done for
+            // `Patch<T>`
             #[cfg(feature = "utoipa")]
-            if utoipa_flag {
+            if options.utoipa == Some(true) {
+                if options.to_schema {
+                    field
+                        .attrs
+                        .push(match (markers.accept_null, options.openapi) {
+                            (false, _) => parse_quote! {
+                                #[schema(nullable = false)]
+                            },
+                            (true, OpenApiVersion::V3_0) => {
+                                utoipa_nullable(&schema_inner_type, false, options)
+                            }
+                            (true, OpenApiVersion::V3_1) => parse_quote! {
+                                #[schema(value_type = Option<#schema_inner_type>, required = false)]
+                            },
+                        })
+                }
+                if options.into_params {
+                    field.attrs.push(utoipa_param(
+                        &schema_inner_type,
+                        markers.accept_null,
+                        false,
+                        options,
+                    ))
+                }
+            }
+            #[cfg(feature = "schemars")]
+            if options.schemars {
+                schemars_with(
+                    field,
+                    if markers.accept_null {
+                        parse_quote!(::core::option::Option<#schema_inner_type>)
+                    } else {
+                        schema_inner_type
+                    },
+                );
+            }
+        } else if nullable && !not_required {
+            // Binary formats write `None` with a presence tag, so no sentinel is needed
+            if let Some(sentinel) = markers.sentinel.as_ref().filter(|_| !options.binary_safe) {
+                let attr = sentinel_helpers(
+                    helper_prefix,
+                    sentinel,
+                    markers.sentinel_out,
+                    SentinelField::Option,
+                    options,
+                    extra_items,
+                );
+                field.attrs.push(attr);
+            } else if let Some(NullRepr::Str(repr)) = &options.null_repr {
+                let attr = sentinel_helpers(
+                    helper_prefix,
+                    &quote!(Str(#repr)),
+                    true,
+                    SentinelField::Option,
+                    options,
+                    extra_items,
+                );
+                field.attrs.push(attr);
+            } else {
+                let with = if options.form {
+                    options.crate_item("__private::form")
+                } else if options.xml {
+                    options.crate_item("__private::xml")
+                } else if assumed {
+                    options.crate_item("__private::nullable")
+                } else {
+                    parse_quote!("::core::option::Option")
+                };
                 field.attrs.push(parse_quote! {
-                    #[schema(required = true)]
-                })
+                    #[serde(with = #with)]
+                });
+            }
+            // Without `null`, `None` is left out, and filled back in when missing
+            let omitted = matches!(options.null_repr, Some(NullRepr::Omit));
+            if omitted && default {
+                field.attrs.push(parse_quote! {
+                    #[serde(skip_serializing_if = #is_none)]
+                });
+            } else if omitted {
+                field.attrs.push(parse_quote! {
+                    #[serde(default, skip_serializing_if = #is_none)]
+                });
+            }
+            #[cfg(feature = "utoipa")]
+            if options.utoipa == Some(true) {
+                // A field with `#[serde(default)]` may be left out, and its default value is
+                // documented if it is given by a function
+                let required = !default && !omitted;
+                let default_fn = field_attribute_value(field, "serde", "default")
+                    .and_then(|default_fn| default_fn.parse::<syn::ExprPath>().ok());
+                // `utoipa` ignores `default` next to `schema_with`, so OpenAPI 3.0 schemas get
+                // their default from a helper
+                let default_schema = match (options.openapi, &default_fn) {
+                    (OpenApiVersion::V3_0, Some(default_fn)) => Some(utoipa_default_schema(
+                        helper_prefix,
+                        &schema_inner_type,
+                        default_fn,
+                        options,
+                        extra_items,
+                    )?),
+                    _ => None,
+                };
+                if options.to_schema {
+                    field.attrs.push(match (options.openapi, default_fn) {
+                        (OpenApiVersion::V3_0, _) => match &default_schema {
+                            Some(schema_with) => parse_quote! {
+                                #[schema(schema_with = #schema_with, required = #required)]
+                            },
+                            None => utoipa_nullable(&schema_inner_type, required, options),
+                        },
+                        (OpenApiVersion::V3_1, Some(default_fn)) => parse_quote! {
+                            #[schema(required = #required, default = #default_fn)]
+                        },
+                        (OpenApiVersion::V3_1, None) => parse_quote! {
+                            #[schema(required = #required)]
+                        },
+                    })
+                }
+                if options.into_params {
+                    field.attrs.push(match &default_schema {
+                        Some(schema_with) => parse_quote! {
+                            #[param(schema_with = #schema_with)]
+                        },
+                        None => utoipa_param(&schema_inner_type, true, required, options),
+                    })
+                }
+            }
+            #[cfg(feature = "schemars")]
+            if options.schemars {
+                let crate_path = &options.crate_path;
+                schemars_with(
+                    field,
+                    parse_quote!(#crate_path::__private::schemars::Nullable<#schema_inner_type>),
+                );
             }
         } else if nullable && not_required {
-            field.attrs.push(parse_quote! {
-                #[serde(default, skip_serializing_if = "Option::is_none",
-                with = "serde_with::rust::double_option")]
-            });
+            if let Some(NullRepr::Omit) = options.null_repr {
+                return Err(omit_error("`None` and `Some(None)`"));
+            }
+            if let Some(NullRepr::Str(repr)) = &options.null_repr {
+                field.attrs.push(parse_quote! {
+                    #[serde(default, skip_serializing_if = #is_none)]
+                });
+                let attr = sentinel_helpers(
+                    helper_prefix,
+                    &quote!(Str(#repr)),
+                    true,
+                    SentinelField::DoubleOption,
+                    options,
+                    extra_items,
+                );
+                field.attrs.push(attr);
+            } else {
+                let with = if options.form {
+                    options.crate_item("__private::form::double_option")
+                } else if options.binary_safe {
+                    options.crate_item("__private::nullable")
+                } else if options.xml {
+                    options.crate_item("__private::xml::double_option")
+                } else {
+                    options.crate_item("__private::double_option")
+                };
+                field.attrs.push(parse_quote! {
+                    #[serde(default, skip_serializing_if = #is_none, with = #with)]
+                });
+            }
+            #[cfg(feature = "utoipa")]
+            if options.utoipa == Some(true) {
+                // Describe the field as a single `Option<T>`, as is done for `Patch<T>`, so
+                // that `null` is only part of the schema once
+                let known_value_type = inner_type.as_ref().and_then(get_std_option);
+                let value_type = known_value_type.clone().unwrap_or_else(|| {
+                    let crate_path = &options.crate_path;
+                    parse_quote! {
+                        <#schema_inner_type as #crate_path::__private::OptionLike>::Inner
+                    }
+                });
+                if options.to_schema {
+                    field.attrs.push(match (options.openapi, known_value_type) {
+                        (OpenApiVersion::V3_0, _) => utoipa_nullable(&value_type, false, options),
+                        (OpenApiVersion::V3_1, Some(value_type)) => parse_quote! {
+                            #[schema(value_type = Option<#value_type>, required = false)]
+                        },
+                        (OpenApiVersion::V3_1, None) => parse_quote! {
+                            #[schema(required = false)]
+                        },
+                    })
+                }
+                if options.into_params {
+                    field
+                        .attrs
+                        .push(utoipa_param(&value_type, true, false, options))
+                }
+            }
+            // The inner type is itself an `Option`, so the schema is already nullable
+            #[cfg(feature = "schemars")]
+            if options.schemars {
+                schemars_with(field, schema_inner_type);
+            }
+        } else if !skipped {
+            // Fields without markers are a regular `Option`, which `utoipa` describes with a
+            // `null` type
+            #[cfg(feature = "utoipa")]
+            if options.utoipa == Some(true) && options.openapi == OpenApiVersion::V3_0 {
+                if options.to_schema {
+                    field
+                        .attrs
+                        .push(utoipa_nullable(&schema_inner_type, false, options))
+                }
+                if options.into_params {
+                    field
+                        .attrs
+                        .push(utoipa_param(&schema_inner_type, true, false, options))
+                }
+            }
+        }
+        // Fields without markers are deserialized as a regular `Option`, unless customized, as
+        // are `#[not_required]` fields with `binary_safe`
+        Presence {
+            nullable: nullable
+                || (!not_required && !customized)
+                || markers.accept_null
+                || (not_required && options.binary_safe),
+            not_required,
         }
     } else {
-        // Error on use of `#[nullable]` or `#[not_required]` on non-Option fields
-        if nullable {
-            return Err("`#[nullable]` may only be used on fields of type `Option<T>`.".into());
+        // Error on use of `#[nullable]` on non-Option fields, unless `null` is mapped to the
+        // default value
+        if markers.sentinel.is_some() {
+            return Err(
+                "`#[nullable(sentinel = ...)]` may only be used on fields of type `Option<T>`"
+                    .into(),
+            );
+        } else if nullable && markers.as_default {
+            process_null_as_default(field, !not_required, options)?;
+        } else if nullable {
+            return Err(
+                "`#[nullable]` may only be used on fields of type `Option<T>`.
+help: use `#[nullable(as_default)]` to deserialize `null` as the default value"
+                    .into(),
+            );
+        }
+        if markers.accept_null {
+            return Err(
+                "`#[not_required(accept_null)]` may only be used on fields of type `Option<T>`
+help: use `#[nullable(as_default)]` together with `#[not_required]` to accept both a missing \
+                value and `null`"
+                    .into(),
+            );
         }
         if not_required {
-            return Err("`#[not_required]` may only be used on fields of type `Option<T>`.".into());
+            process_defaulted_field(field, &markers, helper_prefix, options, extra_items)?;
+        }
+        // Missing values are filled in by `#[serde(default)]`
+        Presence {
+            nullable,
+            not_required: false,
+        }
+    };
+    Ok(presence)
+}
+
+/// The `#[schema(...)]` attribute describing a field as a nullable `value_type` in OpenAPI 3.0,
+/// i.e. with `nullable: true` instead of a `null` type
+#[cfg(feature = "utoipa")]
+fn utoipa_nullable(value_type: &Type, required: bool, options: &ContainerOptions) -> Attribute {
+    let crate_path = &options.crate_path;
+    parse_quote! {
+        #[schema(
+            schema_with = #crate_path::__private::utoipa::nullable::<#value_type>,
+            required = #required
+        )]
+    }
+}
+
+/// Generates the `schema_with` helper of a nullable field in OpenAPI 3.0, which documents the
+/// value of `default_fn` as its default, and returns the path to the helper
+#[cfg(feature = "utoipa")]
+fn utoipa_default_schema(
+    helper_prefix: &str,
+    value_type: &Type,
+    default_fn: &syn::ExprPath,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> Result<syn::ExprPath, String> {
+    let crate_path = &options.crate_path;
+    let utoipa = quote!(#crate_path::__private::utoipa);
+    let helper = format_ident!("__serde_option_schema_{helper_prefix}");
+    let function = quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        fn #helper() -> #utoipa::RefOr<#utoipa::Schema> {
+            #utoipa::nullable_with_default::<#value_type, _>(#default_fn)
+        }
+    };
+    associated_helper(&helper, function, options, extra_items)
+        .parse()
+        .map_err(|err| err.to_string())
+}
+
+/// The `#[param(...)]` attribute for structs deriving `IntoParams`, which doesn't read the
+/// `#[schema(...)]` attributes. `value_type` is the `T` in `Option<T>`, which is only needed
+/// for OpenAPI 3.0.
+///
+/// `IntoParams` ignores `required` for parameters with `schema_with`, so required parameters
+/// keep the OpenAPI 3.1 style `null` type.
+#[cfg(feature = "utoipa")]
+fn utoipa_param(
+    value_type: &Type,
+    nullable: bool,
+    required: bool,
+    options: &ContainerOptions,
+) -> Attribute {
+    let crate_path = &options.crate_path;
+    if nullable && !required && options.openapi == OpenApiVersion::V3_0 {
+        parse_quote! {
+            #[param(schema_with = #crate_path::__private::utoipa::nullable::<#value_type>)]
+        }
+    } else {
+        parse_quote! {
+            #[param(required = #required, nullable = #nullable)]
         }
     }
-    Ok(())
+}
+
+/// Describe the field as `schema_type` to `schemars`, which would otherwise use the type given
+/// in `#[serde(with = "...")]`. `schemars` still reads `default` and `skip_serializing_if` from
+/// the serde attributes to decide whether the field is required.
+///
+/// Fields that already have a custom schema are left untouched.
+#[cfg(feature = "schemars")]
+fn schemars_with(field: &mut Field, schema_type: Type) {
+    if field_has_attribute(field, "schemars", "with")
+        || field_has_attribute(field, "schemars", "schema_with")
+    {
+        return;
+    }
+    let schema_type = LitStr::new(&quote!(#schema_type).to_string(), Span::call_site());
+    field.attrs.push(parse_quote! {
+        #[schemars(with = #schema_type)]
+    });
+}
+
+/// The `#[serde(bound(...))]` attribute for a field which is assumed to be an `Option`, e.g.
+/// `T: OptionLike, <T as OptionLike>::Inner: Serialize` for a generic parameter `T`. With
+/// `double`, the inner type has to be an `Option` as well.
+fn option_like_bounds(ty: &Type, double: bool, options: &ContainerOptions) -> Attribute {
+    let option_like = options.crate_item("__private::OptionLike").value();
+    let serde = options.crate_item("__private::serde").value();
+    let ty = quote!(#ty);
+    // The inner type of `Option<Option<T>>` fields is an `Option` itself
+    let inner = if double {
+        format!("{option_like} + ")
+    } else {
+        String::new()
+    };
+    let bound = |trait_: &str| {
+        format!("{ty}: {option_like}, <{ty} as {option_like}>::Inner: {inner}{serde}::{trait_}")
+    };
+    let serialize = bound("Serialize");
+    let deserialize = bound("Deserialize<'de>");
+    parse_quote! {
+        #[serde(bound(serialize = #serialize, deserialize = #deserialize))]
+    }
+}
+
+/// Whether `tokens` refer to any of `idents`, e.g. to a generic parameter inside a field type
+fn mentions_any(tokens: proc_macro2::TokenStream, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// Emits a warning pointing to `field` with `#[serde_option(strict = warn)]`.
+///
+/// Procedural macros can't emit warnings on stable Rust, so this uses a deprecated constant,
+/// which is immediately used with the span of the field.
+fn strict_warning(field: &Field) -> proc_macro2::TokenStream {
+    let usage = Ident::new("unannotated_option_field", field.span());
+    quote! {
+        const _: () = {
+            #[deprecated(note = #STRICT_MESSAGE)]
+            #[allow(non_upper_case_globals)]
+            const unannotated_option_field: () = ();
+            #usage
+        };
+    }
 }
 
 /// Determine if the `field` has an attribute with given `namespace` and `name`
 ///
 /// On the example of `#[serde(default = "example")]`, `serde` is the namespace and `default` is the name.
 fn field_has_attribute(field: &Field, namespace: &str, name: &str) -> bool {
-    for attr in &field.attrs {
-        if attr.path().is_ident(namespace) {
-            let mut attribute_found = false;
-            attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident(name) {
-                    attribute_found = true;
-                }
-                Ok(())
-            })
-            .unwrap_or(());
-            return attribute_found;
-        }
+    attrs_have_attribute(&field.attrs, namespace, name)
+}
+
+/// Determine if the attribute list has an attribute with given `namespace` and `name`
+fn attrs_have_attribute(attrs: &[Attribute], namespace: &str, name: &str) -> bool {
+    let mut attribute_found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(namespace)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                attribute_found = true;
+            }
+            skip_meta_value(&meta)
+        })
+        .unwrap_or(());
+    }
+    attribute_found
+}
+
+/// Returns the string given to an attribute, e.g. `"path"` for `#[serde(default = "path")]`
+#[cfg(feature = "utoipa")]
+fn field_attribute_value(field: &Field, namespace: &str, name: &str) -> Option<LitStr> {
+    let mut value = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(namespace))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) && meta.input.peek(Token![=]) {
+                value = Some(meta.value()?.parse::<LitStr>()?);
+                return Ok(());
+            }
+            skip_meta_value(&meta)
+        })
+        .unwrap_or(());
     }
-    false
+    value
+}
+
+/// Consume the value of a nested meta item, i.e. `= value` or `(...)`, so that parsing can
+/// continue with the next item
+fn skip_meta_value(meta: &ParseNestedMeta) -> Result<(), Error> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(token::Paren) {
+        meta.input.parse::<TokenTree>()?;
+    }
+    Ok(())
 }
 
 /// Returns the type `T` whenever the type path refers to `std::option::Option<T>`.
@@ -312,23 +1901,74 @@ fn get_std_option(type_: &Type) -> Option<Type> {
                 None
             };
             // Extract the `T` from `Option<T>` if possible.
-            if let Some(PathArguments::AngleBracketed(AngleBracketedGenericArguments {
-                args,
-                ..
-            })) = generic_args
-            {
-                if args.len() == 1 {
-                    if let GenericArgument::Type(ty) = &args[0] {
-                        return Some(ty.clone());
-                    }
-                }
+            generic_args.and_then(single_type_argument)
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` whenever the last segment of the type path is one of `aliases`, as given
+/// with `#[serde_option(option_aliases(...))]`
+fn is_option_alias(type_: &Type, aliases: &[Ident]) -> bool {
+    match type_ {
+        Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) => {
+            is_option_alias(elem, aliases)
+        }
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| aliases.contains(&segment.ident)),
+        _ => false,
+    }
+}
+
+/// Returns the type `T` whenever the type path refers to `serde_option::Patch<T>`.
+/// Returns `None` otherwise, e.g. for other types named `Patch`.
+///
+/// # Accepts
+///
+/// * `serde_option::Patch<T>`, with or without leading `::`
+/// * `<crate>::Patch<T>`, where `<crate>` is the path given with `#[serde_option(crate = "...")]`
+/// * `Alias<T>`, where `Alias` is listed in `#[serde_option(patch_aliases(...))]`
+fn get_patch(type_: &Type, options: &ContainerOptions) -> Option<Type> {
+    match type_ {
+        Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) => {
+            get_patch(elem, options)
+        }
+        Type::Path(TypePath { qself: None, path }) => {
+            let segments: Vec<_> = path.segments.iter().collect();
+            let (last, prefix) = segments.split_last()?;
+            let is_patch = if prefix.is_empty() {
+                path.leading_colon.is_none() && options.patch_aliases.contains(&last.ident)
+            } else {
+                let prefix = prefix.iter().map(|segment| &segment.ident);
+                let crate_path = options.crate_path.segments.iter();
+                last.ident == "Patch"
+                    && (prefix.clone().eq(crate_path.map(|segment| &segment.ident))
+                        || prefix.eq(["serde_option"]))
             };
-            None
+            if is_patch {
+                single_type_argument(&last.arguments)
+            } else {
+                None
+            }
         }
         _ => None,
     }
 }
 
+/// Returns the type `T` if the path arguments are exactly `<T>`
+fn single_type_argument(arguments: &PathArguments) -> Option<Type> {
+    if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = arguments {
+        if args.len() == 1 {
+            if let GenericArgument::Type(ty) = &args[0] {
+                return Some(ty.clone());
+            }
+        }
+    }
+    None
+}
+
 /// Merge multiple [`syn::Error`] into one.
 trait IteratorExt {
     fn merge_errors(self) -> Result<(), Error>
@@ -348,47 +1988,541 @@ trait IteratorExt {
 }
 impl<I> IteratorExt for I where I: Iterator<Item = Result<(), Error>> + Sized {}
 
-/// Handle a single struct or a single enum variant
-fn process_fields(fields: &mut Fields, utoipa_flag: bool) -> Result<(), Error> {
-    match fields {
-        // simple, no fields, do nothing
-        Fields::Unit => Ok(()),
-        Fields::Named(ref mut fields) => fields
-            .named
-            .iter_mut()
-            .map(|field| {
-                process_optional_field(field, utoipa_flag)
-                    .map_err(|err| Error::new(field.span(), err))
-            })
-            .merge_errors(),
-        Fields::Unnamed(ref mut fields) => fields
-            .unnamed
-            .iter_mut()
-            .map(|field| {
-                process_optional_field(field, utoipa_flag)
-                    .map_err(|err| Error::new(field.span(), err))
+/// Handle a single struct or a single enum variant, returning the information about its fields.
+///
+/// `helper_prefix` tells the variant apart within an enum, and is used for the names of the
+/// helper functions generated for its fields.
+fn process_fields(
+    fields: &mut Fields,
+    options: &ContainerOptions,
+    rename_rule: Option<RenameRule>,
+    container_default: bool,
+    helper_prefix: &str,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> Result<Vec<FieldInfo>, Error> {
+    let mut infos = Vec::new();
+    fields
+        .iter_mut()
+        .enumerate()
+        .map(|(index, field)| {
+            let helper_prefix = match &field.ident {
+                Some(ident) => format!("{helper_prefix}{}", ident.unraw()),
+                None => format!("{helper_prefix}{index}"),
+            };
+            let presence = process_optional_field(field, &helper_prefix, options, extra_items)
+                .map_err(|err| Error::new(field.span(), err))?;
+            if options.binary_safe {
+                binary_safe::strip_skip_predicates(field);
+            }
+            let info = FieldInfo::new(field, index, presence, rename_rule, container_default);
+            if info.unwrapped && !options.binary_safe {
+                let helper = format_ident!("__serde_option_deserialize_{helper_prefix}");
+                let function = non_null_deserializer(&helper, &info.deserialize_names[0], options);
+                let helper_path = associated_helper(&helper, function, options, extra_items);
+                field.attrs.push(parse_quote! {
+                    #[serde(deserialize_with = #helper_path)]
+                });
+            }
+            infos.push(info);
+            Ok(())
+        })
+        .merge_errors()?;
+    Ok(infos)
+}
+
+/// The helper function deserializing a `#[not_required]` field, which reports `null` values
+/// as `serde_option::ErrorKind::NullNotAllowed` for the field `name`
+fn non_null_deserializer(
+    helper: &Ident,
+    name: &str,
+    options: &ContainerOptions,
+) -> proc_macro2::TokenStream {
+    let crate_path = &options.crate_path;
+    let private = quote!(#crate_path::__private);
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        fn #helper<'de, __D, __O>(deserializer: __D) -> ::core::result::Result<__O, __D::Error>
+        where
+            __D: #private::serde::Deserializer<'de>,
+            __O: #private::OptionLike,
+            __O::Inner: #private::serde::Deserialize<'de>,
+        {
+            #private::unwrap_or_skip::deserialize_field(deserializer, #name)
+        }
+    }
+}
+
+/// Applies `#[not_required]` on a field which isn't an `Option`, which is filled in with its
+/// `Default` when missing, and skipped during serialization when it is equal to the default.
+/// With `#[not_required(empty)]`, empty values are skipped instead, and `skip_if` replaces the
+/// comparison with a custom predicate.
+fn process_defaulted_field(
+    field: &mut Field,
+    markers: &FieldMarkers,
+    helper_prefix: &str,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> Result<(), String> {
+    if field_has_attribute(field, "serde", "skip") {
+        return Err("`#[not_required]` cannot be used in combination with `#[serde(skip)]`".into());
+    } else if field_has_attribute(field, "serde", "default") {
+        return Err(
+            "`#[not_required]` cannot be used in combination with `#[serde(default)]`".into(),
+        );
+    } else if field_has_attribute(field, "serde", "skip_serializing_if") {
+        return Err(
+            "`#[not_required]` cannot be used in combination with `#[serde(skip_serializing_if)]`
+help: use `#[not_required(skip_if = \"...\")]` to customize which values are skipped"
+                .into(),
+        );
+    } else if markers.empty && markers.skip_if.is_some() {
+        return Err(
+            "`#[not_required(empty)]` cannot be used in combination with `skip_if = \"...\"`"
+                .into(),
+        );
+    }
+    // Binary formats need every field to be written
+    if options.binary_safe {
+        field.attrs.push(parse_quote!(#[serde(default)]));
+        return Ok(());
+    }
+    let helper = format_ident!("__serde_option_skip_{helper_prefix}");
+    let skip_serializing_if = match &markers.skip_if {
+        Some(skip_if) => skip_predicate(
+            &helper,
+            &field.ty,
+            quote!(#skip_if(value)),
+            options,
+            extra_items,
+        ),
+        None if markers.empty => skip_predicate(
+            &helper,
+            &field.ty,
+            quote!(value.is_empty()),
+            options,
+            extra_items,
+        ),
+        None => options.crate_item("__private::is_default"),
+    };
+    field.attrs.push(parse_quote! {
+        #[serde(default, skip_serializing_if = #skip_serializing_if)]
+    });
+    Ok(())
+}
+
+/// Applies `#[nullable(as_default)]` on a field which isn't an `Option`, which deserializes
+/// `null` as the `Default` of the type. The field is serialized as usual.
+// `required` is only needed for the `utoipa` attributes
+#[cfg_attr(not(feature = "utoipa"), allow(unused_variables))]
+fn process_null_as_default(
+    field: &mut Field,
+    required: bool,
+    options: &ContainerOptions,
+) -> Result<(), String> {
+    if field_has_attribute(field, "serde", "skip") {
+        return Err("`#[nullable]` cannot be used in combination with `#[serde(skip)]`".into());
+    } else if field_has_attribute(field, "serde", "with")
+        || field_has_attribute(field, "serde", "deserialize_with")
+    {
+        return Err(
+            "`#[nullable(as_default)]` cannot be used in combination with \
+            `#[serde(with)]` or `#[serde(deserialize_with)]`"
+                .into(),
+        );
+    }
+    // Binary formats write the value as it is, without a presence tag
+    if !options.binary_safe {
+        let deserialize_with = if options.form {
+            options.crate_item("__private::form::deserialize_or_default")
+        } else {
+            options.crate_item("__private::null_as_default::deserialize")
+        };
+        field.attrs.push(parse_quote! {
+            #[serde(deserialize_with = #deserialize_with)]
+        });
+    }
+    let ty = field.ty.clone();
+    #[cfg(feature = "utoipa")]
+    if options.utoipa == Some(true) {
+        if options.to_schema {
+            field.attrs.push(match options.openapi {
+                OpenApiVersion::V3_0 => utoipa_nullable(&ty, required, options),
+                OpenApiVersion::V3_1 => parse_quote! {
+                    #[schema(value_type = Option<#ty>, required = #required)]
+                },
             })
-            .merge_errors(),
+        }
+        if options.into_params {
+            field.attrs.push(utoipa_param(&ty, true, required, options))
+        }
+    }
+    #[cfg(feature = "schemars")]
+    if options.schemars {
+        let crate_path = &options.crate_path;
+        schemars_with(
+            field,
+            parse_quote!(#crate_path::__private::schemars::Nullable<#ty>),
+        );
+    }
+    Ok(())
+}
+
+/// The kind of field whose `null` is replaced by a sentinel, which determines the helpers used
+#[derive(Clone, Copy)]
+enum SentinelField {
+    /// `Option<T>`, where the sentinel is `None`
+    Option,
+    /// `Option<Option<T>>` with both markers, where the sentinel is `Some(None)`
+    DoubleOption,
+    /// `Patch<T>`, where the sentinel is `Patch::Null`
+    Patch,
+}
+
+/// Generates the helpers of a field with a sentinel, as given with `#[nullable(sentinel = ...)]`
+/// or `#[serde_option(null_repr = "...")]`, which deserialize the `sentinel` as `null`, and
+/// serialize `null` as the sentinel if `sentinel_out` is given. `sentinel_out` is required for
+/// fields other than `SentinelField::Option`. Returns the `#[serde(...)]` attribute using them.
+fn sentinel_helpers(
+    helper_prefix: &str,
+    sentinel: &proc_macro2::TokenStream,
+    sentinel_out: bool,
+    kind: SentinelField,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> Attribute {
+    let crate_path = &options.crate_path;
+    let private = quote!(#crate_path::__private);
+    let sentinel = quote!(#private::sentinel::Sentinel::#sentinel);
+    let (module, param, value) = match kind {
+        // XML describes elements as maps, so the sentinel is compared with their text
+        SentinelField::Option if options.xml => {
+            (quote!(#private::sentinel::xml), quote!(__O), quote!(__O))
+        }
+        SentinelField::Option => (quote!(#private::sentinel), quote!(__O), quote!(__O)),
+        SentinelField::DoubleOption => (
+            quote!(#private::sentinel::double_option),
+            quote!(__O),
+            quote!(__O),
+        ),
+        SentinelField::Patch => (
+            quote!(#private::sentinel::patch),
+            quote!(__T),
+            quote!(#crate_path::Patch<__T>),
+        ),
+    };
+    let bounds = |trait_: proc_macro2::TokenStream| match kind {
+        SentinelField::Option => quote!(__O: #private::OptionLike, __O::Inner: #trait_),
+        SentinelField::DoubleOption => quote! {
+            __O: #private::OptionLike,
+            __O::Inner: #private::OptionLike,
+            <__O::Inner as #private::OptionLike>::Inner: #trait_
+        },
+        SentinelField::Patch => quote!(__T: #trait_),
+    };
+    let deserialize = format_ident!("__serde_option_sentinel_deserialize_{helper_prefix}");
+    let deserialize_bounds = bounds(quote!(#private::serde::Deserialize<'de>));
+    let function = quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        fn #deserialize<'de, __D, #param>(
+            deserializer: __D,
+        ) -> ::core::result::Result<#value, __D::Error>
+        where
+            __D: #private::serde::Deserializer<'de>,
+            #deserialize_bounds,
+        {
+            #module::deserialize(deserializer, #sentinel)
+        }
+    };
+    let deserialize = associated_helper(&deserialize, function, options, extra_items);
+    let serialize = if sentinel_out {
+        let serialize = format_ident!("__serde_option_sentinel_serialize_{helper_prefix}");
+        let serialize_bounds = bounds(quote!(#private::serde::Serialize));
+        let function = quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            fn #serialize<#param, __S>(
+                value: &#value,
+                serializer: __S,
+            ) -> ::core::result::Result<__S::Ok, __S::Error>
+            where
+                #serialize_bounds,
+                __S: #private::serde::Serializer,
+            {
+                #module::serialize(value, serializer, #sentinel)
+            }
+        };
+        associated_helper(&serialize, function, options, extra_items)
+    } else {
+        options.crate_item("__private::nullable::serialize")
+    };
+    parse_quote! {
+        #[serde(deserialize_with = #deserialize, serialize_with = #serialize)]
+    }
+}
+
+/// Generates the `skip_serializing_if` helper `helper` for a field, which evaluates `condition`
+/// for the field's `value`, and returns the path to the helper.
+///
+/// The helper takes the field type itself, so that methods of the concrete type can be called.
+fn skip_predicate(
+    helper: &Ident,
+    ty: &Type,
+    condition: proc_macro2::TokenStream,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> LitStr {
+    let function = quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case, clippy::ptr_arg)]
+        fn #helper(value: &#ty) -> bool {
+            #condition
+        }
+    };
+    associated_helper(helper, function, options, extra_items)
+}
+
+/// Emits the helper `function` named `helper` as an associated function of the item, so that
+/// it can't collide with the helpers of other items, and returns the path to it
+fn associated_helper(
+    helper: &Ident,
+    function: proc_macro2::TokenStream,
+    options: &ContainerOptions,
+    extra_items: &mut Vec<proc_macro2::TokenStream>,
+) -> LitStr {
+    let ident = &options.ident;
+    let (impl_generics, ty_generics, where_clause) = options.generics.split_for_impl();
+    extra_items.push(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #function
+        }
+    });
+    let turbofish = ty_generics.as_turbofish();
+    LitStr::new(
+        &quote!(#ident #turbofish::#helper)
+            .to_string()
+            .replace(' ', ""),
+        Span::call_site(),
+    )
+}
+
+/// Enable the `utoipa` attributes if the item derives `ToSchema` or `IntoParams`, unless this
+/// was given explicitly with `#[serde_option(utoipa = ...)]`
+fn detect_derives(attrs: &[Attribute], options: &mut ContainerOptions) {
+    let derives: Vec<Ident> = derived_traits(attrs).collect();
+    let to_schema = derives.iter().any(|derive| derive == "ToSchema");
+    options.into_params = derives.iter().any(|derive| derive == "IntoParams");
+    options
+        .utoipa
+        .get_or_insert(to_schema || options.into_params);
+    // `#[schema(...)]` is only accepted by `ToSchema`, which is assumed if `utoipa` is given
+    // explicitly and neither derive is found, e.g. when it is derived through `cfg_attr`
+    options.to_schema = to_schema || !options.into_params;
+}
+
+/// The names of the traits in the `#[derive(...)]` attributes, e.g. `ToSchema` for
+/// `#[derive(utoipa::ToSchema)]`
+fn derived_traits(attrs: &[Attribute]) -> impl Iterator<Item = Ident> + '_ {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .filter_map(|path| path.segments.last().map(|segment| segment.ident.clone()))
+}
+
+/// The names of the generic type parameters of an item
+fn type_params(generics: &Generics) -> Vec<Ident> {
+    generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect()
+}
+
+/// Forward the `serde` crate path as `#[serde(crate = "...")]`, unless the container already
+/// has one
+fn process_container_attrs(attrs: &mut Vec<Attribute>, options: &ContainerOptions) {
+    if let Some(serde_path) = &options.serde_path {
+        if !attrs_have_attribute(attrs, "serde", "crate") {
+            let serde_path = quote!(#serde_path).to_string().replace(' ', "");
+            attrs.push(parse_quote!(#[serde(crate = #serde_path)]));
+        }
+    }
+}
+
+/// The processed `item` followed by the generated `extra_items`, which are put in an anonymous
+/// `const` under the `#[cfg]` attributes of the item, so that they are left out along with it
+fn expansion(
+    item: &impl ToTokens,
+    attrs: &[Attribute],
+    extra_items: Vec<proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let cfgs = attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
+    quote! {
+        #item
+        #(#cfgs)*
+        const _: () = {
+            #(#extra_items)*
+        };
     }
 }
 
 /// Apply function on every field of structs or enums
 fn process_items(
     input: proc_macro::TokenStream,
-    utoipa_flag: bool,
+    options: &mut ContainerOptions,
 ) -> Result<proc_macro2::TokenStream, Error> {
+    let mut extra_items = Vec::new();
     // Process the top level fields in structs
     if let Ok(mut input) = syn::parse::<ItemStruct>(input.clone()) {
-        process_fields(&mut input.fields, utoipa_flag)?;
-        Ok(quote!(#input))
+        detect_derives(&input.attrs, options);
+        options.ident = input.ident.clone();
+        options.generics = input.generics.clone();
+        process_container_attrs(&mut input.attrs, options);
+        let fields = process_fields(
+            &mut input.fields,
+            options,
+            RenameRule::from_attrs(&input.attrs, "rename_all"),
+            fields::has_container_default(&input.attrs),
+            "",
+            &mut extra_items,
+        )?;
+        let crate_path = &options.crate_path;
+        let specs = fields.iter().filter_map(|field| field.spec(crate_path));
+        extra_items.push(fields::fields_impl(
+            &input.ident,
+            &input.generics,
+            quote!(const FIELDS: &'static [#crate_path::FieldSpec] = &[#(#specs),*];),
+            crate_path,
+        ));
+        #[cfg(feature = "json_schema")]
+        if options.json_schema {
+            let deny_unknown_fields =
+                attrs_have_attribute(&input.attrs, "serde", "deny_unknown_fields");
+            let body =
+                json_schema::fields_schema(&input.fields, &fields, deny_unknown_fields, crate_path)
+                    .unwrap_or_else(|| quote!(#crate_path::__private::json_schema::unit()));
+            extra_items.push(json_schema::json_schema_impl(
+                &input.ident,
+                &input.attrs,
+                &input.generics,
+                &fields.iter().collect::<Vec<_>>(),
+                body,
+                crate_path,
+            ));
+        }
+        #[cfg(feature = "validate")]
+        if options.validate {
+            extra_items.push(validate::validate_impl(
+                &input.ident,
+                &input.generics,
+                &fields.iter().collect::<Vec<_>>(),
+                validate::fields_validation(&input.fields, &fields, crate_path),
+                crate_path,
+            ));
+        }
+        Ok(expansion(&input, &input.attrs, extra_items))
     // Process the fields inside enum variants
     } else if let Ok(mut input) = syn::parse::<ItemEnum>(input) {
+        detect_derives(&input.attrs, options);
+        options.ident = input.ident.clone();
+        options.generics = input.generics.clone();
+        process_container_attrs(&mut input.attrs, options);
+        let variant_rule = RenameRule::from_attrs(&input.attrs, "rename_all");
+        let fields_rule = RenameRule::from_attrs(&input.attrs, "rename_all_fields");
+        let mut variants = Vec::new();
+        let mut variant_fields = Vec::new();
         input
             .variants
             .iter_mut()
-            .map(|variant| process_fields(&mut variant.fields, utoipa_flag))
+            .enumerate()
+            .map(|(index, variant)| {
+                let rename_rule =
+                    RenameRule::from_attrs(&variant.attrs, "rename_all").or(fields_rule);
+                let helper_prefix = format!("{index}_");
+                let fields = process_fields(
+                    &mut variant.fields,
+                    options,
+                    rename_rule,
+                    false,
+                    &helper_prefix,
+                    &mut extra_items,
+                )?;
+                variants.push(fields::variant_spec(
+                    variant,
+                    &fields,
+                    variant_rule,
+                    &options.crate_path,
+                ));
+                variant_fields.push(fields);
+                Ok(())
+            })
             .merge_errors()?;
-        Ok(quote!(#input))
+        let crate_path = &options.crate_path;
+        extra_items.push(fields::fields_impl(
+            &input.ident,
+            &input.generics,
+            quote!(const VARIANTS: &'static [#crate_path::VariantSpec] = &[#(#variants),*];),
+            crate_path,
+        ));
+        #[cfg(feature = "json_schema")]
+        if options.json_schema {
+            let tagging = fields::Tagging::from_attrs(&input.attrs);
+            let schemas = input
+                .variants
+                .iter()
+                .zip(&variant_fields)
+                .filter(|(variant, _)| !fields::is_skipped_variant(variant))
+                .map(|(variant, fields)| {
+                    let deny_unknown_fields =
+                        attrs_have_attribute(&input.attrs, "serde", "deny_unknown_fields");
+                    let body = json_schema::fields_schema(
+                        &variant.fields,
+                        fields,
+                        deny_unknown_fields,
+                        crate_path,
+                    );
+                    json_schema::variant_schema(variant, body, &tagging, variant_rule, crate_path)
+                });
+            let body = quote! {
+                #crate_path::__private::json_schema::one_of(::std::vec![#(#schemas),*])
+            };
+            extra_items.push(json_schema::json_schema_impl(
+                &input.ident,
+                &input.attrs,
+                &input.generics,
+                &variant_fields.iter().flatten().collect::<Vec<_>>(),
+                body,
+                crate_path,
+            ));
+        }
+        #[cfg(feature = "validate")]
+        if options.validate {
+            let tagging = fields::Tagging::from_attrs(&input.attrs);
+            let variants = input
+                .variants
+                .iter()
+                .zip(&variant_fields)
+                .filter(|(variant, _)| !fields::is_skipped_variant(variant))
+                .map(|(variant, fields)| {
+                    let validation =
+                        validate::fields_validation(&variant.fields, fields, crate_path);
+                    (variant, validation)
+                })
+                .collect();
+            extra_items.push(validate::validate_impl(
+                &input.ident,
+                &input.generics,
+                &variant_fields.iter().flatten().collect::<Vec<_>>(),
+                validate::enum_validation(variants, &tagging, variant_rule, crate_path),
+                crate_path,
+            ));
+        }
+        Ok(expansion(&input, &input.attrs, extra_items))
     } else {
         Err(Error::new(
             Span::call_site(),
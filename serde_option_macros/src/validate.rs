@@ -0,0 +1,170 @@
+//! Generates the `ValidatePresence` implementation of the `validate` feature, which checks a
+//! `serde_json::Value` against the presence rules of the fields.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Fields, Generics, Ident, Path, Variant};
+
+use crate::fields::{variant_name, FieldInfo, RenameRule, Tagging};
+use crate::mentions_any;
+
+/// The statements validating a struct or variant body. `value`, `path` and `errors` are
+/// expected to be in scope.
+///
+/// Skipped fields are left out, and flattened fields are validated against the same object.
+pub(crate) fn fields_validation(
+    fields: &Fields,
+    infos: &[FieldInfo],
+    crate_path: &Path,
+) -> TokenStream {
+    let helpers = quote!(#crate_path::__private::validate);
+    let infos = infos.iter().filter(|info| !info.skipped);
+    match fields {
+        Fields::Unit => TokenStream::new(),
+        Fields::Named(_) => {
+            let checks = infos.map(|info| {
+                let nested = nested(info, crate_path);
+                if info.flattened {
+                    return quote!(#nested.validate_nested(value, path, errors););
+                }
+                let names = &info.deserialize_names;
+                let required = info.required;
+                let nullable = info.nullable;
+                quote! {
+                    if let ::core::option::Option::Some((value, path)) = #helpers::check_field(
+                        object,
+                        path,
+                        &[#(#names),*],
+                        #required,
+                        #nullable || #nested.accepts_null(),
+                        errors,
+                    ) {
+                        #nested.validate_nested(value, &path, errors);
+                    }
+                }
+            });
+            quote! {
+                if let ::core::option::Option::Some(object) = value.as_object() {
+                    #(#checks)*
+                }
+            }
+        }
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let checks = infos.map(|info| {
+                let nested = nested(info, crate_path);
+                quote!(#nested.validate_nested(value, path, errors);)
+            });
+            quote!(#(#checks)*)
+        }
+        Fields::Unnamed(_) => {
+            let checks = infos.enumerate().map(|(index, info)| {
+                let nested = nested(info, crate_path);
+                quote! {
+                    if let ::core::option::Option::Some((value, path)) =
+                        #helpers::tuple_item(value, path, #index)
+                    {
+                        #nested.validate_nested(value, &path, errors);
+                    }
+                }
+            });
+            quote!(#(#checks)*)
+        }
+    }
+}
+
+/// The statements validating an enum, given the validation of each variant's body.
+///
+/// Untagged enums are not validated, since the variant can't be told from the value.
+pub(crate) fn enum_validation(
+    variants: Vec<(&Variant, TokenStream)>,
+    tagging: &Tagging,
+    rename_rule: Option<RenameRule>,
+    crate_path: &Path,
+) -> TokenStream {
+    let helpers = quote!(#crate_path::__private::validate);
+    let variant = match tagging {
+        Tagging::External => quote!(#helpers::external(value, path)),
+        Tagging::Internal(tag) => quote!(#helpers::internal(value, path, #tag)),
+        Tagging::Adjacent(tag, content) => {
+            quote!(#helpers::adjacent(value, path, #tag, #content))
+        }
+        Tagging::Untagged => return TokenStream::new(),
+    };
+    let arms = variants.into_iter().map(|(variant, validation)| {
+        let name = variant_name(variant, rename_rule);
+        quote!(#name => { #validation })
+    });
+    quote! {
+        if let ::core::option::Option::Some((name, value, path)) = #variant {
+            let (value, path): (&#crate_path::__private::serde_json::Value, &str) = (&value, &path);
+            match name {
+                #(#arms)*
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The expression dispatching to the `ValidatePresence` implementation of the field's value
+/// type, if there is one
+fn nested(info: &FieldInfo, crate_path: &Path) -> TokenStream {
+    let ty = info.value_type(crate_path);
+    quote!((&&#crate_path::__private::validate::Nested::<#ty>::new()))
+}
+
+/// The `ValidatePresence` implementation and the inherent `validate_presence` function of an
+/// item, whose validation is given by `body`.
+///
+/// Fields which are only assumed to be an `Option` get an `OptionLike` bound if they depend on
+/// generic parameters, so that their inner type can be named.
+pub(crate) fn validate_impl(
+    ident: &Ident,
+    generics: &Generics,
+    fields: &[&FieldInfo],
+    body: TokenStream,
+    crate_path: &Path,
+) -> TokenStream {
+    let type_params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for info in fields.iter().filter(|info| info.unwrapped && !info.skipped) {
+        let ty = &info.ty;
+        if mentions_any(ty.to_token_stream(), &type_params) {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: #crate_path::__private::OptionLike));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let value = quote!(#crate_path::__private::serde_json::Value);
+    let error = quote!(#crate_path::validate::PresenceError);
+    quote! {
+        impl #impl_generics #crate_path::validate::ValidatePresence for #ident #ty_generics
+        #where_clause
+        {
+            #[allow(unused_variables)]
+            fn validate_at(
+                value: &#value,
+                path: &str,
+                errors: &mut ::std::vec::Vec<#error>,
+            ) {
+                #[allow(unused_imports)]
+                use #crate_path::__private::validate::{SkipNested as _, ValidateNested as _};
+                #body
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Check a JSON value against the presence rules of this type, reporting every
+            /// missing required field and every unexpected `null`
+            pub fn validate_presence(
+                value: &#value,
+            ) -> ::core::result::Result<(), ::std::vec::Vec<#error>> {
+                #crate_path::validate::validate_presence::<Self>(value)
+            }
+        }
+    }
+}
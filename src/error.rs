@@ -0,0 +1,118 @@
+use core::fmt;
+
+/// A violation of the presence rules of a field.
+///
+/// The helpers generated by the [`macro@crate::serde_option`] macro reject `null` values of
+/// `#[not_required]` fields with the message of [`ErrorKind::NullNotAllowed`]. Missing required
+/// fields, such as `#[nullable]` fields, are reported by serde itself, with the message of the
+/// data format, which the macro can't replace. [`Error::parse`] recognizes both messages on a
+/// best-effort basis, so that the cases can be told apart, e.g. to map them to a
+/// `422 Unprocessable Entity` response.
+///
+/// Missing fields are only recognized by serde's wording, ``missing field `name` ``, so they
+/// aren't covered for formats which word them differently, or where serde replaces the message,
+/// such as for `#[serde(untagged)]` enums. The `validate` feature checks a `serde_json::Value`
+/// before deserializing it instead, and reports every violation with its full path.
+///
+/// ```
+/// # use serde::Deserialize;
+/// use serde_option::{serde_option, Error, ErrorKind};
+///
+/// #[serde_option]
+/// #[derive(Debug, Deserialize)]
+/// struct User {
+///     #[nullable]
+///     name: Option<String>,
+///     #[not_required]
+///     email: Option<String>,
+/// }
+///
+/// let err = serde_json::from_str::<User>(r#"{"name": null, "email": null}"#).unwrap_err();
+/// assert_eq!(
+///     Error::parse(&err.to_string()),
+///     Some(Error::new("/email", ErrorKind::NullNotAllowed))
+/// );
+///
+/// let err = serde_json::from_str::<User>("{}").unwrap_err();
+/// assert_eq!(
+///     Error::parse(&err.to_string()),
+///     Some(Error::new("/name", ErrorKind::MissingRequiredField))
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Error {
+    /// [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) to the field, e.g. `/email`.
+    /// Errors raised during deserialization only know the field itself, not the path to the
+    /// containing value.
+    pub path: String,
+    /// Which rule was violated
+    pub kind: ErrorKind,
+}
+
+/// The presence rule violated by a field
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A required field is missing
+    MissingRequiredField,
+    /// A field which isn't nullable is `null`
+    NullNotAllowed,
+}
+
+/// The prefix of the message serde uses for missing fields
+const SERDE_MISSING_FIELD: &str = "missing field `";
+/// The prefix and suffix of the messages of [`ErrorKind::MissingRequiredField`]
+const MISSING_REQUIRED_FIELD: (&str, &str) = ("missing required field `", "`");
+/// The prefix and suffix of the messages of [`ErrorKind::NullNotAllowed`]
+const NULL_NOT_ALLOWED: (&str, &str) = ("field `", "` must not be null");
+
+impl Error {
+    /// Create an error for the field at the JSON pointer `path`
+    pub fn new(path: impl Into<String>, kind: ErrorKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+
+    /// Recognize the message of a deserialization error, as produced by this error type or by
+    /// serde for missing fields. Trailing context added by the data format, such as the line
+    /// and column, is ignored.
+    ///
+    /// This is a best-effort parser of the message, which depends on serde's wording of missing
+    /// fields, and returns `None` for formats which word them differently. The path of the
+    /// result only names the field itself, not the values containing it.
+    pub fn parse(message: &str) -> Option<Self> {
+        let between = |(prefix, suffix): (&str, &str)| {
+            let rest = message.strip_prefix(prefix)?;
+            rest.find(suffix).map(|end| rest[..end].to_owned())
+        };
+        if let Some(path) = between(MISSING_REQUIRED_FIELD) {
+            Some(Self::new(path, ErrorKind::MissingRequiredField))
+        } else if let Some(path) = between(NULL_NOT_ALLOWED) {
+            Some(Self::new(path, ErrorKind::NullNotAllowed))
+        } else {
+            let field = between((SERDE_MISSING_FIELD, "`"))?;
+            Some(Self::new(
+                pointer("", &field),
+                ErrorKind::MissingRequiredField,
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, suffix) = match self.kind {
+            ErrorKind::MissingRequiredField => MISSING_REQUIRED_FIELD,
+            ErrorKind::NullNotAllowed => NULL_NOT_ALLOWED,
+        };
+        write!(f, "{prefix}{}{suffix}", self.path)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Append a reference token to a JSON pointer, escaping `~` and `/`
+pub fn pointer(path: &str, token: &str) -> String {
+    format!("{path}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
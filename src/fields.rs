@@ -0,0 +1,63 @@
+/// Presence rules of the fields of a type, as (de)serialized by serde.
+///
+/// This trait is implemented by the [`macro@crate::serde_option`] macro for every struct and
+/// enum it is applied to. It allows inspecting which fields are required and which are nullable
+/// at runtime, e.g. to build documentation or validation without a schema library.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// use serde_option::{serde_option, FieldSpec, SerdeOptionFields};
+///
+/// #[serde_option]
+/// #[derive(Deserialize, Serialize)]
+/// #[serde(rename_all = "camelCase")]
+/// struct User {
+///     #[nullable]
+///     nick_name: Option<String>,
+///     #[not_required]
+///     email: Option<String>,
+/// }
+///
+/// assert_eq!(User::FIELDS[0].name, "nickName");
+/// assert_eq!(User::FIELDS[0].ident, "nick_name");
+/// assert!(User::FIELDS[0].nullable && User::FIELDS[0].required);
+/// assert!(!User::FIELDS[1].nullable && !User::FIELDS[1].required);
+/// ```
+pub trait SerdeOptionFields {
+    /// The fields of a struct, in declaration order. Empty for enums.
+    const FIELDS: &'static [FieldSpec] = &[];
+
+    /// The variants of an enum, in declaration order. Empty for structs.
+    const VARIANTS: &'static [VariantSpec] = &[];
+}
+
+/// Describes a single field of a struct or enum variant.
+///
+/// Fields with `#[serde(flatten)]` are not included, since they don't have a name of their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+    /// The serialized name, after applying `#[serde(rename)]` and `#[serde(rename_all)]`
+    pub name: &'static str,
+    /// The name of the field in Rust, or its index for tuple structs and variants
+    pub ident: &'static str,
+    /// Whether `null` is accepted for the field
+    pub nullable: bool,
+    /// Whether the field must be present when deserializing
+    pub required: bool,
+    /// Whether the field is skipped with `#[serde(skip)]` or `#[serde(skip_deserializing)]`
+    pub skipped: bool,
+    /// Whether missing values are filled in with a default, either from `#[serde(default)]`
+    /// or from `#[not_required]`
+    pub has_default: bool,
+}
+
+/// Describes a single variant of an enum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VariantSpec {
+    /// The serialized name, after applying `#[serde(rename)]` and `#[serde(rename_all)]`
+    pub name: &'static str,
+    /// The name of the variant in Rust
+    pub ident: &'static str,
+    /// The fields of the variant, in declaration order
+    pub fields: &'static [FieldSpec],
+}
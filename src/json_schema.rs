@@ -0,0 +1,314 @@
+//! JSON Schemas (draft 2020-12) generated with `#[serde_option(json_schema)]`, without
+//! depending on a schema library.
+//!
+//! The macro implements [`JsonSchema`] for the item, and adds an inherent
+//! `fn json_schema() -> serde_json::Value` returning its root schema. `#[nullable]` fields
+//! accept `null`, and only fields which serde requires to be present are listed in `required`.
+//! Nested types compose through their own [`JsonSchema`] implementation, so they have to use
+//! the macro as well, or implement the trait by hand.
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! use serde_json::json;
+//! use serde_option::serde_option;
+//!
+//! #[serde_option(json_schema)]
+//! #[derive(Deserialize, Serialize)]
+//! struct Address {
+//!     #[not_required]
+//!     city: Option<String>,
+//! }
+//!
+//! #[serde_option(json_schema)]
+//! #[derive(Deserialize, Serialize)]
+//! struct User {
+//!     #[nullable]
+//!     address: Option<Address>,
+//! }
+//!
+//! let schema = User::json_schema();
+//! assert_eq!(schema["required"], json!(["address"]));
+//! assert_eq!(schema["properties"]["address"]["type"], json!(["object", "null"]));
+//! assert_eq!(schema["properties"]["address"]["properties"]["city"], json!({"type": "string"}));
+//! ```
+//!
+//! Schemas of nested types are inlined. Recursive types are referenced through `$defs` instead,
+//! which are collected at the root of the schema.
+
+use core::any::type_name;
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::Patch;
+
+/// The `$schema` of the generated root schemas
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A type which can be described by a JSON Schema.
+///
+/// This is implemented by `#[serde_option(json_schema)]`, and for common types of the standard
+/// library.
+pub trait JsonSchema {
+    /// The schema of the type. Named types are registered with the `generator`, so that
+    /// recursive types can be referenced.
+    fn schema(generator: &mut SchemaGenerator) -> Value;
+}
+
+/// Keeps track of the named types while a schema is generated, see [`schema_for`]
+#[derive(Debug, Default)]
+pub struct SchemaGenerator {
+    /// Schemas of the recursive types, by their name in `$defs`
+    defs: Map<String, Value>,
+    /// `type_name`s of the recursive types, with their name in `$defs`
+    names: Vec<(&'static str, String)>,
+    /// `type_name`s of the types whose schema is being generated
+    in_progress: Vec<&'static str>,
+}
+
+impl SchemaGenerator {
+    /// The schema of `T`
+    pub fn subschema<T: JsonSchema + ?Sized>(&mut self) -> Value {
+        T::schema(self)
+    }
+
+    /// The schema of the named type `T`, as built by `build`.
+    ///
+    /// The schema is inlined, unless `T` is already being generated further up, in which case
+    /// it is referenced as `#/$defs/<name>`.
+    pub fn named_schema<T: ?Sized>(
+        &mut self,
+        name: &str,
+        build: impl FnOnce(&mut Self) -> Value,
+    ) -> Value {
+        let type_name = type_name::<T>();
+        if self.in_progress.contains(&type_name) {
+            return json!({ "$ref": format!("#/$defs/{}", self.def_name(type_name, name)) });
+        }
+        self.in_progress.push(type_name);
+        let schema = build(self);
+        self.in_progress.pop();
+        if let Some((_, def_name)) = self.names.iter().find(|(known, _)| *known == type_name) {
+            self.defs.insert(def_name.clone(), schema.clone());
+        }
+        schema
+    }
+
+    /// The name of a recursive type in `$defs`, which is made unique by a numeric suffix, e.g.
+    /// for different instantiations of a generic type
+    fn def_name(&mut self, type_name: &'static str, name: &str) -> String {
+        if let Some((_, def_name)) = self.names.iter().find(|(known, _)| *known == type_name) {
+            return def_name.clone();
+        }
+        let mut def_name = name.to_owned();
+        let mut suffix = 1;
+        while self.names.iter().any(|(_, known)| *known == def_name) {
+            suffix += 1;
+            def_name = format!("{name}{suffix}");
+        }
+        self.names.push((type_name, def_name.clone()));
+        def_name
+    }
+
+    /// Turn the schema of the root type into a standalone schema, with `$schema` and `$defs`
+    pub fn into_root_schema(self, schema: Value) -> Value {
+        let mut root = Map::new();
+        root.insert("$schema".into(), DRAFT_2020_12.into());
+        match schema {
+            Value::Object(schema) => root.extend(schema),
+            schema => {
+                root.insert("allOf".into(), json!([schema]));
+            }
+        }
+        if !self.defs.is_empty() {
+            root.insert("$defs".into(), Value::Object(self.defs));
+        }
+        Value::Object(root)
+    }
+}
+
+/// The root schema of `T`
+pub fn schema_for<T: JsonSchema + ?Sized>() -> Value {
+    let mut generator = SchemaGenerator::default();
+    let schema = T::schema(&mut generator);
+    generator.into_root_schema(schema)
+}
+
+/// Make a schema accept `null` as well, by adding `null` to its `type` if possible
+pub fn nullable(mut schema: Value) -> Value {
+    let null_type = Value::from("null");
+    if let Value::Object(object) = &mut schema {
+        if object.is_empty() {
+            // The schema already accepts anything
+            return schema;
+        }
+        let restricted = object.contains_key("enum") || object.contains_key("const");
+        match object.get_mut("type") {
+            Some(Value::String(type_)) if *type_ == "null" => return schema,
+            Some(type_ @ Value::String(_)) if !restricted => {
+                *type_ = json!([type_.take(), null_type]);
+                return schema;
+            }
+            Some(Value::Array(types)) if !restricted => {
+                if !types.contains(&null_type) {
+                    types.push(null_type);
+                }
+                return schema;
+            }
+            _ => {}
+        }
+        let any_of = object.get("anyOf").and_then(Value::as_array);
+        if any_of.is_some_and(|any_of| any_of.contains(&json!({ "type": "null" }))) {
+            return schema;
+        }
+    }
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+macro_rules! simple_schema {
+    ($($type_:ty => $schema:tt,)*) => {
+        $(
+            impl JsonSchema for $type_ {
+                fn schema(_: &mut SchemaGenerator) -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+simple_schema! {
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer" },
+    i16 => { "type": "integer" },
+    i32 => { "type": "integer" },
+    i64 => { "type": "integer" },
+    i128 => { "type": "integer" },
+    isize => { "type": "integer" },
+    u8 => { "type": "integer", "minimum": 0 },
+    u16 => { "type": "integer", "minimum": 0 },
+    u32 => { "type": "integer", "minimum": 0 },
+    u64 => { "type": "integer", "minimum": 0 },
+    u128 => { "type": "integer", "minimum": 0 },
+    usize => { "type": "integer", "minimum": 0 },
+    f32 => { "type": "number" },
+    f64 => { "type": "number" },
+    char => { "type": "string", "minLength": 1, "maxLength": 1 },
+    str => { "type": "string" },
+    String => { "type": "string" },
+    () => { "type": "null" },
+    Value => {},
+    Map<String, Value> => { "type": "object" },
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        nullable(T::schema(generator))
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Patch<T> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        nullable(T::schema(generator))
+    }
+}
+
+macro_rules! wrapper_schema {
+    ($($type_:ty,)*) => {
+        $(
+            impl<T: JsonSchema + ?Sized> JsonSchema for $type_ {
+                fn schema(generator: &mut SchemaGenerator) -> Value {
+                    T::schema(generator)
+                }
+            }
+        )*
+    };
+}
+
+wrapper_schema! {
+    &T,
+    &mut T,
+    Box<T>,
+    Rc<T>,
+    Arc<T>,
+}
+
+impl<T: JsonSchema + ToOwned + ?Sized> JsonSchema for Cow<'_, T> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        T::schema(generator)
+    }
+}
+
+macro_rules! array_schema {
+    ($($type_:ty => $unique:literal,)*) => {
+        $(
+            impl<T: JsonSchema> JsonSchema for $type_ {
+                fn schema(generator: &mut SchemaGenerator) -> Value {
+                    let mut schema = json!({ "type": "array", "items": T::schema(generator) });
+                    if $unique {
+                        schema["uniqueItems"] = true.into();
+                    }
+                    schema
+                }
+            }
+        )*
+    };
+}
+
+array_schema! {
+    [T] => false,
+    Vec<T> => false,
+    VecDeque<T> => false,
+    LinkedList<T> => false,
+    BTreeSet<T> => true,
+}
+
+impl<T: JsonSchema, S> JsonSchema for HashSet<T, S> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        BTreeSet::<T>::schema(generator)
+    }
+}
+
+impl<T: JsonSchema, const N: usize> JsonSchema for [T; N] {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        json!({
+            "type": "array",
+            "items": T::schema(generator),
+            "minItems": N,
+            "maxItems": N,
+        })
+    }
+}
+
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema(generator) })
+    }
+}
+
+impl<K, V: JsonSchema, S> JsonSchema for HashMap<K, V, S> {
+    fn schema(generator: &mut SchemaGenerator) -> Value {
+        BTreeMap::<K, V>::schema(generator)
+    }
+}
+
+macro_rules! tuple_schema {
+    ($($name:ident)+) => {
+        impl<$($name: JsonSchema),+> JsonSchema for ($($name,)+) {
+            fn schema(generator: &mut SchemaGenerator) -> Value {
+                crate::__private::json_schema::tuple(vec![$($name::schema(generator)),+])
+            }
+        }
+    };
+}
+
+tuple_schema!(A);
+tuple_schema!(A B);
+tuple_schema!(A B C);
+tuple_schema!(A B C D);
+tuple_schema!(A B C D E);
+tuple_schema!(A B C D E F);
+tuple_schema!(A B C D E F G);
+tuple_schema!(A B C D E F G H);
@@ -1,5 +1,22 @@
 #![doc = include_str!("../README.md")]
 
+// Allows the macro expansions, which refer to `::serde_option`, to be used inside this crate
+extern crate self as serde_option;
+
+#[doc(hidden)]
+#[path = "private.rs"]
+pub mod __private;
+mod error;
+mod fields;
+#[cfg(feature = "json_schema")]
+pub mod json_schema;
+mod patch;
+#[cfg(feature = "validate")]
+pub mod validate;
+
+pub use error::{Error, ErrorKind};
+pub use fields::{FieldSpec, SerdeOptionFields, VariantSpec};
+pub use patch::Patch;
 pub use serde_option_macros::serde_option;
 
 // This module structure exists to allow unit tests. Currently it's not possible
@@ -20,6 +37,19 @@ mod tests {
         t.compile_fail("test/ui/skip_nullable.rs");
         t.compile_fail("test/ui/skip_not_required.rs");
         t.compile_fail("test/ui/default_not_required.rs");
+        t.compile_fail("test/ui/conflicting_markers.rs");
+        t.compile_fail("test/ui/strict_unannotated.rs");
+        t.compile_fail("test/ui/unsupported_marker_argument.rs");
+        t.compile_fail("test/ui/skip_if_skip_serializing_if.rs");
+        t.compile_fail("test/ui/empty_option.rs");
+        t.compile_fail("test/ui/accept_null_nullable.rs");
+        t.compile_fail("test/ui/accept_null_option_alias.rs");
+        t.compile_fail("test/ui/sentinel_out_without_sentinel.rs");
+        t.compile_fail("test/ui/form_sentinel.rs");
+        t.compile_fail("test/ui/binary_safe_form.rs");
+        t.compile_fail("test/ui/null_repr_omit_double_option.rs");
+        t.compile_fail("test/ui/xml_null_repr.rs");
+        t.compile_fail("test/ui/single_option_both_markers.rs");
     }
 
     #[test]
@@ -62,6 +92,312 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_null_repr() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(null_repr = "null")]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            nullable: Option<u64>,
+            #[not_required]
+            optional: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both: Option<Option<u64>>,
+            patch: serde_option::Patch<String>,
+        }
+
+        #[serde_option(null_repr = omit)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Omitted {
+            #[nullable]
+            nullable: Option<u64>,
+            #[not_required]
+            optional: Option<u64>,
+        }
+
+        let accepted = [
+            json!({"nullable": 1}),
+            json!({"nullable": null}),
+            json!({"nullable": 1, "optional": 2}),
+            json!({"nullable": 1, "optional": 2, "both": null, "patch": null}),
+            json!({"nullable": 1, "optional": 2, "both": 3, "patch": "a"}),
+        ];
+        for json in accepted {
+            let model: Example =
+                serde_json::from_value(json.clone()).expect("Deserialization should work");
+            let serialized = toml::to_string(&model).expect("Serialization should work");
+            assert_eq!(
+                model,
+                toml::from_str(&serialized).expect("Roundtrip should work"),
+                "Roundtrip of {json} should be equal"
+            );
+
+            let model: Omitted =
+                serde_json::from_value(json.clone()).expect("Deserialization should work");
+            let serialized = toml::to_string(&model).expect("Serialization should work");
+            assert_eq!(
+                model,
+                toml::from_str(&serialized).expect("Roundtrip should work"),
+                "Roundtrip of {json} should be equal"
+            );
+        }
+
+        let model = Example {
+            nullable: None,
+            optional: None,
+            both: Some(None),
+            patch: Patch::Null,
+        };
+        assert_eq!(
+            toml::to_string(&model).expect("Serialization should work"),
+            "nullable = \"null\"\nboth = \"null\"\npatch = \"null\"\n",
+            "`null` should be written as the string"
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"nullable": "null", "both": "null", "patch": "null"}),
+            "The string should be used by every format"
+        );
+        assert!(
+            toml::from_str::<Example>("").is_err(),
+            "Nullable fields should still be required"
+        );
+
+        let omitted = Omitted {
+            nullable: None,
+            optional: None,
+        };
+        assert_eq!(
+            toml::to_string(&omitted).expect("Serialization should work"),
+            "",
+            "`null` should be left out"
+        );
+        assert_eq!(
+            serde_json::from_value::<Omitted>(json!({"nullable": null}))
+                .expect("Deserialization should work"),
+            omitted,
+            "`null` should still be accepted"
+        );
+    }
+
+    #[test]
+    fn test_binary_roundtrip_serialization() {
+        use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+        #[serde_option(binary_safe)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug, Default)]
+        struct Example<T: Serialize + DeserializeOwned> {
+            #[nullable]
+            nullable: Option<u64>,
+            #[not_required]
+            optional: Option<T>,
+            #[nullable]
+            #[not_required]
+            both: Option<Option<u64>>,
+            #[not_required(accept_null)]
+            lenient: Option<String>,
+            #[not_required(skip_if = "String::is_empty")]
+            non_empty: Option<String>,
+            #[not_required]
+            defaulted: u32,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            custom: Vec<u8>,
+            patch: serde_option::Patch<u64>,
+            #[nullable(sentinel = -1, sentinel_out)]
+            sentinel: Option<i64>,
+            #[nullable(as_default)]
+            as_default: String,
+        }
+
+        #[serde_option(binary_safe)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        enum Message {
+            Update {
+                #[not_required]
+                id: Option<u64>,
+                patch: serde_option::Patch<String>,
+            },
+            Delete(#[not_required] Option<u64>),
+        }
+
+        fn roundtrip<T>(value: &T)
+        where
+            T: Serialize + DeserializeOwned + PartialEq + core::fmt::Debug,
+        {
+            let bytes = bincode::serialize(value).expect("Serialization should work");
+            assert_eq!(
+                &bincode::deserialize::<T>(&bytes).expect("Deserialization should work"),
+                value,
+                "bincode roundtrip should be equal"
+            );
+            let bytes = postcard::to_allocvec(value).expect("Serialization should work");
+            assert_eq!(
+                &postcard::from_bytes::<T>(&bytes).expect("Deserialization should work"),
+                value,
+                "postcard roundtrip should be equal"
+            );
+        }
+
+        let examples = [
+            Example::default(),
+            Example {
+                nullable: Some(1),
+                optional: Some(true),
+                both: Some(None),
+                lenient: Some("a".to_owned()),
+                non_empty: Some(String::new()),
+                defaulted: 2,
+                custom: vec![3],
+                patch: Patch::Null,
+                sentinel: Some(-1),
+                as_default: "b".to_owned(),
+            },
+            Example {
+                optional: Some(false),
+                both: Some(Some(4)),
+                patch: Patch::Value(5),
+                ..Example::default()
+            },
+        ];
+        for example in &examples {
+            roundtrip(example);
+        }
+        roundtrip(&Message::Update {
+            id: None,
+            patch: Patch::Absent,
+        });
+        roundtrip(&Message::Update {
+            id: Some(1),
+            patch: Patch::Null,
+        });
+        roundtrip(&Message::Delete(None));
+        roundtrip(&Message::Delete(Some(2)));
+
+        // The fields are never skipped, in any format
+        assert_eq!(
+            serde_json::to_value(&examples[0]).expect("Serialization should work"),
+            json!({
+                "nullable": null,
+                "optional": null,
+                "both": null,
+                "lenient": null,
+                "non_empty": null,
+                "defaulted": 0,
+                "custom": [],
+                "patch": null,
+                "sentinel": null,
+                "as_default": ""
+            }),
+            "Absent fields should be written"
+        );
+        assert_eq!(
+            serde_json::from_value::<Example<bool>>(
+                json!({"nullable": null, "sentinel": null, "as_default": ""})
+            )
+            .expect("Deserialization should work"),
+            examples[0],
+            "Missing fields should still be filled in"
+        );
+        let patch_null = Message::Update {
+            id: None,
+            patch: Patch::Null,
+        };
+        assert_eq!(
+            serde_json::from_value::<Message>(
+                serde_json::to_value(&patch_null).expect("Serialization should work")
+            )
+            .expect("Deserialization should work"),
+            Message::Update {
+                id: None,
+                patch: Patch::Absent,
+            },
+            "JSON can't tell `Patch::Null` apart from `Patch::Absent`"
+        );
+        let optional = Example::<bool>::FIELDS
+            .iter()
+            .find(|field| field.name == "optional")
+            .expect("The field should be listed");
+        assert!(
+            optional.nullable && !optional.required,
+            "`#[not_required]` fields should accept `null`"
+        );
+    }
+
+    #[test]
+    fn test_non_human_readable_serialization() {
+        use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            nullable: Option<u64>,
+            #[not_required]
+            optional: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both: Option<Option<u64>>,
+            #[nullable(as_default)]
+            as_default: Vec<u8>,
+            #[nullable(sentinel = "N/A", sentinel_out)]
+            sentinel: Option<u64>,
+        }
+
+        // CBOR isn't human-readable, but has `null` and named fields
+        fn cbor<T: Serialize>(value: &T) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).expect("Serialization should work");
+            bytes
+        }
+
+        fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+            ciborium::from_reader(bytes).map_err(|err| err.to_string())
+        }
+
+        let example = Example {
+            nullable: None,
+            optional: None,
+            both: Some(None),
+            as_default: vec![],
+            sentinel: None,
+        };
+        let bytes = cbor(&example);
+        assert_eq!(
+            from_cbor::<serde_json::Value>(&bytes),
+            Ok(json!({"nullable": null, "both": null, "as_default": [], "sentinel": "N/A"})),
+            "`None` should be skipped, and the sentinel written"
+        );
+        assert_eq!(from_cbor::<Example>(&bytes), Ok(example));
+        assert_eq!(
+            from_cbor::<Example>(&cbor(&json!({
+                "nullable": null,
+                "as_default": null,
+                "sentinel": null,
+            }))),
+            Ok(Example {
+                nullable: None,
+                optional: None,
+                both: None,
+                as_default: vec![],
+                sentinel: None,
+            }),
+            "`null` should be read as in JSON"
+        );
+        assert!(
+            from_cbor::<Example>(&cbor(&json!({
+                "nullable": null,
+                "optional": null,
+                "as_default": [],
+                "sentinel": 1,
+            })))
+            .is_err_and(|err| err.contains("field `/optional` must not be null")),
+            "`null` should be rejected for `optional`"
+        );
+    }
+
     #[test]
     fn test_default_fields() {
         use serde::{Deserialize, Serialize};
@@ -108,6 +444,266 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_patch_fields() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(patch_aliases(Patch))]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            patch: Patch<u64>,
+            #[nullable]
+            #[not_required]
+            annotated: serde_option::Patch<u64>,
+        }
+
+        let cases = [
+            (json!({}), Patch::Absent),
+            (json!({"patch": null}), Patch::Null),
+            (json!({"patch": 1}), Patch::Value(1)),
+        ];
+
+        for (json, expected) in cases {
+            let model: Example =
+                serde_json::from_value(json.clone()).expect("Deserialization should work");
+            assert_eq!(
+                model.patch, expected,
+                "Should deserialize into {expected:?}"
+            );
+            assert_eq!(
+                serde_json::to_value(&model).expect("Serialization should work"),
+                json,
+                "Roundtrip should be equal"
+            );
+        }
+
+        let mut target = Some(1);
+        Patch::Absent.apply_to(&mut target);
+        assert_eq!(target, Some(1), "Absent should leave the target unchanged");
+        Patch::Value(2).apply_to(&mut target);
+        assert_eq!(target, Some(2), "Value should overwrite the target");
+        Patch::Null.apply_to(&mut target);
+        assert_eq!(target, None, "Null should clear the target");
+
+        assert_eq!(Patch::from(Some(None::<u64>)), Patch::Null);
+        assert_eq!(Option::<Option<u64>>::from(Patch::Value(3)), Some(Some(3)));
+        assert_eq!(Patch::Value(3).map(|v| v * 2).into_option(), Some(6));
+    }
+
+    #[test]
+    fn test_other_patch_types() {
+        use serde::{Deserialize, Serialize};
+
+        mod ops {
+            #[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+            pub struct Patch<T> {
+                pub op: String,
+                pub value: T,
+            }
+        }
+        use ops::Patch;
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            patch: Patch<u64>,
+            ops_patch: ops::Patch<String>,
+        }
+
+        let json = json!({
+            "patch": {"op": "add", "value": 1},
+            "ops_patch": {"op": "remove", "value": "a"},
+        });
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+        assert!(
+            serde_json::from_value::<Example>(json!({"ops_patch": null})).is_err(),
+            "Other types named `Patch` should be left as they are"
+        );
+        assert!(
+            Example::FIELDS
+                .iter()
+                .all(|field| !field.nullable && field.required),
+            "Other types named `Patch` should be reported as required"
+        );
+    }
+
+    #[test]
+    fn test_crate_paths() {
+        mod platform {
+            pub use crate as serde_option;
+            pub use ::serde;
+        }
+        use platform::serde::{Deserialize, Serialize};
+
+        #[serde_option(crate = "platform::serde_option", serde = "platform::serde")]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        #[serde(rename_all = "camelCase")]
+        struct Example {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<u64>>,
+            patch_field: platform::serde_option::Patch<u64>,
+        }
+
+        let json = json!({"nullableField": null, "bothField": 1});
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            model,
+            Example {
+                nullable_field: None,
+                not_required_field: None,
+                both_field: Some(Some(1)),
+                patch_field: Patch::Absent,
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+    }
+
+    #[test]
+    fn test_default_presence() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(default = not_required)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            not_required: Option<u64>,
+            #[nullable]
+            both: Option<Option<u64>>,
+            #[required]
+            #[nullable]
+            nullable: Option<u64>,
+            #[serde(default)]
+            with_default: Option<u64>,
+        }
+
+        let model: Example =
+            serde_json::from_value(json!({"nullable": null})).expect("Accepts without values");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"nullable": null, "with_default": null}),
+            "Only fields which are not required should be skipped"
+        );
+
+        serde_json::from_value::<Example>(json!({})).expect_err("Should require `nullable`");
+        serde_json::from_value::<Example>(json!({"nullable": 1, "not_required": null}))
+            .expect_err("Should not accept null for `not_required`");
+
+        let model: Example = serde_json::from_value(json!({"nullable": 1, "both": null}))
+            .expect("Accepts null for `both`");
+        assert_eq!(model.both, Some(None), "Should keep null for `both`");
+
+        #[serde_option(default = both)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        enum Variants {
+            Both {
+                both: Option<Option<u64>>,
+                #[non_nullable]
+                not_required: Option<u64>,
+            },
+        }
+
+        let model: Variants =
+            serde_json::from_value(json!({"Both": {}})).expect("Accepts without values");
+        assert_eq!(
+            model,
+            Variants::Both {
+                both: None,
+                not_required: None
+            }
+        );
+        serde_json::from_value::<Variants>(json!({"Both": {"not_required": null}}))
+            .expect_err("Should not accept null for `not_required`");
+    }
+
+    #[test]
+    fn test_strict() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(strict, default = nullable)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            nullable: Option<u64>,
+            #[not_required]
+            #[non_nullable]
+            not_required: Option<u64>,
+            #[serde(skip)]
+            skipped: Option<u64>,
+            non_option: u64,
+        }
+
+        let model: Example = serde_json::from_value(json!({"nullable": null, "non_option": 1}))
+            .expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"nullable": null, "non_option": 1}),
+        );
+    }
+
+    #[test]
+    fn test_option_aliases() {
+        use serde::{Deserialize, Serialize};
+
+        type Maybe<T> = Option<T>;
+        type OptionalId = Option<u64>;
+
+        #[serde_option(option_aliases(Maybe, OptionalId), strict)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example<T> {
+            #[nullable]
+            alias: Maybe<u64>,
+            #[not_required]
+            id: OptionalId,
+            #[nullable]
+            #[not_required]
+            nested: Maybe<Maybe<u64>>,
+            #[not_required(assume_option)]
+            generic: T,
+            #[nullable]
+            name: Option<String>,
+        }
+
+        let model: Example<Option<u64>> =
+            serde_json::from_value(json!({"alias": null, "name": null}))
+                .expect("Accepts without values");
+        assert_eq!(
+            model,
+            Example {
+                alias: None,
+                id: None,
+                nested: None,
+                generic: None,
+                name: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"alias": null, "name": null}),
+            "Only nullable fields should be serialized"
+        );
+
+        serde_json::from_value::<Example<Option<u64>>>(json!({}))
+            .expect_err("Should require `alias`");
+        serde_json::from_value::<Example<Option<u64>>>(
+            json!({"alias": 1, "name": null, "generic": null}),
+        )
+        .expect_err("Should not accept null for `generic`");
+    }
+
     #[test]
     fn test_skipped() {
         use serde::{Deserialize, Serialize};
@@ -128,31 +724,830 @@ mod tests {
         )
     }
 
-    #[cfg(feature = "utoipa")]
     #[test]
-    fn test_utoipa_features() {
+    fn test_skip_if() {
         use serde::{Deserialize, Serialize};
-        use utoipa::openapi::schema::SchemaType;
-        use utoipa::openapi::{RefOr, Schema};
-        use utoipa::{PartialSchema, ToSchema};
 
-        #[serde_option(utoipa)]
-        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
-        struct Example {
-            #[nullable]
-            nullable_field: Option<u64>,
-            #[not_required]
-            not_required_field: Option<u64>,
+        fn is_blank(value: &str) -> bool {
+            value.trim().is_empty()
         }
 
-        let schema = Example::schema();
-        let RefOr::T(Schema::Object(object)) = schema else {
-            panic!("schema should be an object")
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example<'a, T: Serialize + serde::de::DeserializeOwned> {
+            #[not_required(skip_if = "Vec::is_empty")]
+            tags: Option<Vec<String>>,
+            #[not_required(skip_if = "is_blank")]
+            name: Option<String>,
+            #[not_required(skip_if = "<[T]>::is_empty")]
+            items: Option<Vec<T>>,
+            #[not_required(skip_if = "str::is_empty")]
+            #[serde(borrow)]
+            borrowed: Option<&'a str>,
+        }
+
+        let skipped = Example::<u64> {
+            tags: Some(vec![]),
+            name: Some("  ".to_owned()),
+            items: None,
+            borrowed: Some(""),
         };
-        let Some(RefOr::T(Schema::Object(nullable_field))) =
-            object.properties.get("nullable_field")
-        else {
-            panic!("nullable_field should exist and be an object")
+        assert_eq!(
+            &serde_json::to_string(&skipped).expect("Serialization should work"),
+            "{}",
+            "Values matching the predicate should be skipped"
+        );
+
+        let kept = Example {
+            tags: Some(vec!["a".to_owned()]),
+            name: Some("name".to_owned()),
+            items: Some(vec![1]),
+            borrowed: Some("borrowed"),
+        };
+        let serialized = serde_json::to_string(&kept).expect("Serialization should work");
+        assert_eq!(
+            serialized,
+            r#"{"tags":["a"],"name":"name","items":[1],"borrowed":"borrowed"}"#,
+        );
+        assert_eq!(
+            serde_json::from_str::<Example<u64>>(&serialized).expect("Deserialization should work"),
+            kept,
+        );
+    }
+
+    #[test]
+    #[allow(non_camel_case_types)]
+    fn test_helper_names() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example_first {
+            #[not_required]
+            second: Option<u64>,
+        }
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[not_required]
+            first_second: Option<u64>,
+        }
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        enum Variants {
+            Example_first {
+                #[not_required]
+                second: Option<u64>,
+            },
+            Example {
+                #[not_required]
+                first_second: Option<u64>,
+            },
+        }
+
+        #[serde_option]
+        #[cfg(test)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Branch {
+            #[not_required(skip_if = "str::is_empty")]
+            value: Option<String>,
+        }
+
+        #[serde_option]
+        #[cfg(not(test))]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Branch {
+            #[not_required]
+            value: Option<u64>,
+        }
+
+        assert_eq!(
+            serde_json::from_value::<Example_first>(json!({"second": 1}))
+                .expect("Deserialization should work"),
+            Example_first { second: Some(1) },
+        );
+        assert_eq!(
+            serde_json::from_value::<Example>(json!({"first_second": 1}))
+                .expect("Deserialization should work"),
+            Example {
+                first_second: Some(1)
+            },
+        );
+        assert_eq!(
+            serde_json::from_value::<Variants>(json!({"Example": {}}))
+                .expect("Deserialization should work"),
+            Variants::Example { first_second: None },
+        );
+        let branch = Branch {
+            value: Some(String::new()),
+        };
+        assert_eq!(
+            &serde_json::to_string(&branch).expect("Serialization should work"),
+            "{}",
+            "Only the helpers of the enabled item should be used"
+        );
+    }
+
+    #[test]
+    fn test_not_required_defaults() {
+        use serde::{Deserialize, Serialize};
+        use std::collections::HashMap;
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[not_required]
+            tags: Vec<String>,
+            #[not_required]
+            labels: HashMap<String, String>,
+            #[not_required]
+            enabled: bool,
+            #[not_required(empty)]
+            name: String,
+            #[not_required(skip_if = "is_zero")]
+            count: u64,
+        }
+
+        fn is_zero(value: &u64) -> bool {
+            *value == 0
+        }
+
+        let empty: Example = serde_json::from_value(json!({})).expect("Accepts without values");
+        assert_eq!(
+            empty,
+            Example {
+                tags: vec![],
+                labels: HashMap::new(),
+                enabled: false,
+                name: String::new(),
+                count: 0,
+            },
+            "Missing fields should be filled in with their default"
+        );
+        assert_eq!(
+            serde_json::to_value(&empty).expect("Serialization should work"),
+            json!({}),
+            "Default values should be skipped"
+        );
+
+        let json = json!({
+            "tags": ["a"],
+            "labels": {"key": "value"},
+            "enabled": true,
+            "name": "name",
+            "count": 1,
+        });
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+        assert!(
+            serde_json::from_value::<Example>(json!({"tags": null})).is_err(),
+            "Null should not be accepted"
+        );
+        assert!(
+            Example::FIELDS
+                .iter()
+                .all(|field| !field.required && !field.nullable),
+            "Fields should be reported as not required"
+        );
+    }
+
+    #[test]
+    fn test_nullable_as_default() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable(as_default)]
+            tags: Vec<String>,
+            #[nullable(as_default)]
+            #[not_required]
+            name: String,
+        }
+
+        let model: Example = serde_json::from_value(json!({"tags": null, "name": null}))
+            .expect("Null should be accepted");
+        assert_eq!(
+            model,
+            Example {
+                tags: vec![],
+                name: String::new(),
+            },
+            "Null should be deserialized as the default"
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"tags": []}),
+            "Values should be serialized as usual"
+        );
+        assert!(
+            serde_json::from_value::<Example>(json!({})).is_err(),
+            "Fields should still be required without `#[not_required]`"
+        );
+        let json = json!({"tags": ["a"], "name": "name"});
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+        assert!(Example::FIELDS[0].nullable && Example::FIELDS[0].required);
+        assert!(Example::FIELDS[1].nullable && !Example::FIELDS[1].required);
+    }
+
+    #[test]
+    fn test_accept_null() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(default = both)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[not_required(accept_null)]
+            lenient: Option<u64>,
+            #[not_required(accept_null, skip_if = "str::is_empty")]
+            name: Option<String>,
+        }
+
+        for json in [json!({}), json!({"lenient": null, "name": null})] {
+            let model: Example =
+                serde_json::from_value(json.clone()).expect("Deserialization should work");
+            assert_eq!(
+                model,
+                Example {
+                    lenient: None,
+                    name: None,
+                },
+                "{json} should be deserialized as `None`"
+            );
+            assert_eq!(
+                serde_json::to_value(&model).expect("Serialization should work"),
+                json!({}),
+                "`None` should be skipped"
+            );
+        }
+
+        let json = json!({"lenient": 1, "name": "name"});
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+        assert!(
+            Example::FIELDS
+                .iter()
+                .all(|field| field.nullable && !field.required),
+            "Fields should be reported as nullable and not required"
+        );
+    }
+
+    #[test]
+    fn test_sentinels() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Id(i64);
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable(sentinel = "N/A")]
+            name: Option<String>,
+            #[nullable(sentinel = "N/A", sentinel_out)]
+            count: Option<u64>,
+            #[nullable(sentinel = -1, sentinel_out)]
+            id: Option<Id>,
+            #[nullable(sentinel = 0.0)]
+            ratio: Option<f64>,
+        }
+
+        let model: Example = serde_json::from_value(json!({
+            "name": "N/A",
+            "count": "N/A",
+            "id": -1,
+            "ratio": 0.0,
+        }))
+        .expect("Sentinels should be accepted");
+        let none = Example {
+            name: None,
+            count: None,
+            id: None,
+            ratio: None,
+        };
+        assert_eq!(model, none, "Sentinels should be deserialized as `None`");
+        assert_eq!(
+            serde_json::from_str::<Example>(
+                r#"{"name": "N/A", "count": "N/A", "id": -1, "ratio": 0.0}"#
+            )
+            .expect("Sentinels should be accepted"),
+            none,
+            "Sentinels should be deserialized as `None` from a string"
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json!({"name": null, "count": "N/A", "id": -1, "ratio": null}),
+            "Only `sentinel_out` should serialize the sentinel"
+        );
+        let model: Example = serde_json::from_value(json!({
+            "name": null,
+            "count": null,
+            "id": null,
+            "ratio": null,
+        }))
+        .expect("Null should be accepted");
+        assert_eq!(model, none, "Null should be deserialized as `None`");
+
+        let json = json!({"name": "name", "count": 1, "id": 2, "ratio": 0.5});
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+
+        let error = serde_json::from_value::<Example>(json!({
+            "name": "name",
+            "count": "many",
+            "id": 2,
+            "ratio": 0.5,
+        }))
+        .expect_err("Other values should be rejected");
+        assert!(
+            error
+                .to_string()
+                .starts_with("invalid type: string \"many\""),
+            "The error of the inner type should be kept, got: {error}"
+        );
+        assert!(
+            serde_json::from_value::<Example>(json!({})).is_err(),
+            "Fields should still be required"
+        );
+    }
+
+    #[test]
+    fn test_numeric_sentinels() {
+        use serde::Deserialize;
+
+        #[serde_option]
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable(sentinel = -1)]
+            int_sentinel: Option<f64>,
+            #[nullable(sentinel = -1.0)]
+            float_sentinel: Option<i64>,
+        }
+
+        let none = Example {
+            int_sentinel: None,
+            float_sentinel: None,
+        };
+        assert_eq!(
+            serde_json::from_str::<Example>(r#"{"int_sentinel": -1.0, "float_sentinel": -1}"#)
+                .expect("Sentinels should be accepted"),
+            none,
+            "Numbers should match sentinels of the other kind by value"
+        );
+        assert_eq!(
+            serde_json::from_str::<Example>(r#"{"int_sentinel": -1, "float_sentinel": -1.0}"#)
+                .map_err(|err| err.to_string()),
+            Ok(none),
+            "Numbers should match sentinels of the same kind"
+        );
+        assert_eq!(
+            serde_json::from_str::<Example>(r#"{"int_sentinel": -1.5, "float_sentinel": -2}"#)
+                .map_err(|err| err.to_string()),
+            Ok(Example {
+                int_sentinel: Some(-1.5),
+                float_sentinel: Some(-2),
+            }),
+            "Other numbers should be kept"
+        );
+    }
+
+    #[test]
+    fn test_compound_sentinels() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Point {
+            x: i64,
+            y: i64,
+        }
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        enum Shape {
+            Empty,
+            Circle(u64),
+        }
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable(sentinel = "N/A")]
+            tags: Option<Vec<String>>,
+            #[nullable(sentinel = "N/A")]
+            point: Option<Point>,
+            #[nullable(sentinel = -1)]
+            pair: Option<(u64, u64)>,
+            #[nullable(sentinel = "N/A")]
+            shape: Option<Shape>,
+        }
+
+        let none = Example {
+            tags: None,
+            point: None,
+            pair: None,
+            shape: None,
+        };
+        assert_eq!(
+            serde_json::from_str::<Example>(
+                r#"{"tags": "N/A", "point": "N/A", "pair": -1, "shape": "N/A"}"#
+            )
+            .map_err(|err| err.to_string()),
+            Ok(none),
+            "Sentinels should be accepted for sequences, structs and enums"
+        );
+
+        let json = json!({
+            "tags": ["a", "b"],
+            "point": {"x": 1, "y": 2},
+            "pair": [3, 4],
+            "shape": "Empty",
+        });
+        let model: Example =
+            serde_json::from_value(json.clone()).expect("Deserialization should work");
+        assert_eq!(
+            model,
+            Example {
+                tags: Some(vec!["a".to_owned(), "b".to_owned()]),
+                point: Some(Point { x: 1, y: 2 }),
+                pair: Some((3, 4)),
+                shape: Some(Shape::Empty),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&model).expect("Serialization should work"),
+            json,
+            "Roundtrip should be equal"
+        );
+        assert_eq!(
+            serde_json::from_str::<Example>(
+                r#"{"tags": [], "point": "N/A", "pair": -1, "shape": {"Circle": 5}}"#
+            )
+            .map_err(|err| err.to_string())
+            .map(|model| model.shape),
+            Ok(Some(Shape::Circle(5))),
+            "Variants with values should be accepted"
+        );
+        assert!(
+            serde_json::from_str::<Example>(
+                r#"{"tags": "none", "point": "N/A", "pair": -1, "shape": "N/A"}"#
+            )
+            .is_err(),
+            "Other values of the wrong type should be rejected"
+        );
+    }
+
+    #[test]
+    fn test_form() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(form)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            name: Option<String>,
+            #[nullable]
+            count: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both: Option<Option<u64>>,
+            #[not_required]
+            page: Option<u64>,
+            #[not_required(accept_null)]
+            lenient: Option<bool>,
+            #[nullable(as_default)]
+            limit: u32,
+            patch: serde_option::Patch<String>,
+        }
+
+        let cases = [
+            (
+                "name=&count=&limit=",
+                Example {
+                    name: None,
+                    count: None,
+                    both: None,
+                    page: None,
+                    lenient: None,
+                    limit: 0,
+                    patch: Patch::Absent,
+                },
+            ),
+            (
+                "name=&count=&both=&limit=0&patch=",
+                Example {
+                    name: None,
+                    count: None,
+                    both: Some(None),
+                    page: None,
+                    lenient: None,
+                    limit: 0,
+                    patch: Patch::Null,
+                },
+            ),
+            (
+                "name=a+b&count=1&both=2&page=3&lenient=true&limit=4&patch=c",
+                Example {
+                    name: Some("a b".to_owned()),
+                    count: Some(1),
+                    both: Some(Some(2)),
+                    page: Some(3),
+                    lenient: Some(true),
+                    limit: 4,
+                    patch: Patch::Value("c".to_owned()),
+                },
+            ),
+        ];
+        for (form, expected) in &cases {
+            let model: Example =
+                serde_urlencoded::from_str(form).expect("Deserialization should work");
+            assert_eq!(&model, expected, "{form} should be deserialized");
+            let serialized =
+                serde_urlencoded::to_string(&model).expect("Serialization should work");
+            assert_eq!(
+                serde_urlencoded::from_str::<Example>(&serialized).expect("Roundtrip should work"),
+                *expected,
+                "Roundtrip of {form} should be equal"
+            );
+            let json = serde_json::to_string(&model).expect("Serialization should work");
+            assert_eq!(
+                serde_json::from_str::<Example>(&json).expect("JSON roundtrip should work"),
+                *expected,
+                "JSON roundtrip of {form} should be equal"
+            );
+        }
+        assert_eq!(
+            serde_json::from_value::<Example>(json!({
+                "name": "a",
+                "count": 1,
+                "both": 2,
+                "page": 3,
+                "lenient": true,
+                "limit": 4,
+                "patch": "c",
+            }))
+            .expect("Deserialization should work"),
+            Example {
+                name: Some("a".to_owned()),
+                count: Some(1),
+                both: Some(Some(2)),
+                page: Some(3),
+                lenient: Some(true),
+                limit: 4,
+                patch: Patch::Value("c".to_owned()),
+            },
+            "Values other than strings should be deserialized as they are"
+        );
+        assert_eq!(
+            serde_urlencoded::to_string(&cases[1].1).expect("Serialization should work"),
+            "name=&count=&both=&limit=0&patch=",
+            "`None` should be serialized as an empty value"
+        );
+        assert!(
+            serde_urlencoded::from_str::<Example>("name=&limit=").is_err(),
+            "Nullable fields should still be required"
+        );
+        assert!(
+            serde_urlencoded::from_str::<Example>("name=&count=&limit=&page=").is_err(),
+            "Empty values of fields which aren't nullable should be rejected"
+        );
+        assert!(
+            serde_urlencoded::from_str::<Example>("name=&count=x&limit=").is_err(),
+            "Invalid values should be rejected"
+        );
+    }
+
+    #[test]
+    fn test_presence_errors() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        #[serde(rename_all = "camelCase")]
+        struct Example {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+        }
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        enum Nested {
+            Variant {
+                #[not_required]
+                r#field: Option<String>,
+            },
+        }
+
+        let error = |json| {
+            let err = serde_json::from_value::<Example>(json).expect_err("Should be rejected");
+            Error::parse(&err.to_string())
+        };
+        assert_eq!(
+            error(json!({"nullableField": 1, "notRequiredField": null})),
+            Some(Error::new("/notRequiredField", ErrorKind::NullNotAllowed))
+        );
+        assert_eq!(
+            error(json!({"notRequiredField": 1})),
+            Some(Error::new(
+                "/nullableField",
+                ErrorKind::MissingRequiredField
+            ))
+        );
+        assert_eq!(error(json!({"nullableField": "text"})), None);
+
+        // Inner types which accept `null` themselves keep it
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct NullInside {
+            #[not_required]
+            value: Option<serde_json::Value>,
+            #[not_required]
+            unit: Option<()>,
+        }
+        assert_eq!(
+            serde_json::from_value::<NullInside>(json!({"value": null, "unit": null}))
+                .expect("Deserialization should work"),
+            NullInside {
+                value: Some(serde_json::Value::Null),
+                unit: Some(()),
+            }
+        );
+        assert_eq!(
+            serde_json::from_value::<NullInside>(json!({})).expect("Deserialization should work"),
+            NullInside {
+                value: None,
+                unit: None,
+            }
+        );
+
+        let err = serde_json::from_str::<Nested>(r#"{"Variant": {"field": null}}"#)
+            .expect_err("Should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "field `/field` must not be null at line 1 column 27"
+        );
+        assert_eq!(
+            serde_json::from_str::<Nested>(r#"{"Variant": {"field": "value"}}"#)
+                .expect("Should accept values"),
+            Nested::Variant {
+                field: Some("value".into())
+            }
+        );
+
+        // Untagged enums replace the message of the variants with their own
+        #[serde_option]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        #[serde(untagged)]
+        enum Untagged {
+            Variant {
+                #[nullable]
+                field: Option<u64>,
+            },
+        }
+        let err = serde_json::from_value::<Untagged>(json!({})).expect_err("Should be rejected");
+        assert_eq!(Error::parse(&err.to_string()), None);
+    }
+
+    #[test]
+    fn test_field_specs() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Example<T> {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            #[nullable]
+            #[not_required]
+            #[serde(rename = "both")]
+            both_field: Option<Option<u64>>,
+            plain_field: Option<u64>,
+            patch_field: serde_option::Patch<u64>,
+            #[serde(skip)]
+            #[allow(dead_code)]
+            skipped_field: u64,
+            #[serde(default)]
+            r#default_field: T,
+        }
+
+        let spec = |name, ident, nullable, required, skipped, has_default| FieldSpec {
+            name,
+            ident,
+            nullable,
+            required,
+            skipped,
+            has_default,
+        };
+        assert_eq!(
+            Example::<u64>::FIELDS,
+            [
+                spec("nullableField", "nullable_field", true, true, false, false),
+                spec(
+                    "notRequiredField",
+                    "not_required_field",
+                    false,
+                    false,
+                    false,
+                    true
+                ),
+                spec("both", "both_field", true, false, false, true),
+                spec("plainField", "plain_field", true, false, false, false),
+                spec("patchField", "patch_field", true, false, false, true),
+                spec("skippedField", "skipped_field", false, false, true, false),
+                spec("defaultField", "default_field", false, false, false, true),
+            ]
+        );
+        assert!(Example::<u64>::VARIANTS.is_empty());
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize)]
+        #[serde(rename_all = "snake_case", rename_all_fields = "PascalCase")]
+        enum Variants {
+            NamedVariant {
+                #[nullable]
+                some_field: Option<u64>,
+            },
+            #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+            RenamedFields {
+                some_field: u64,
+            },
+            #[serde(rename = "tuple")]
+            TupleVariant(#[not_required] Option<u64>),
+            Unit,
+        }
+
+        let names = Variants::VARIANTS
+            .iter()
+            .map(|variant| {
+                let fields = variant.fields.iter().map(|field| field.name);
+                (variant.name, fields.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("named_variant", vec!["SomeField"]),
+                ("renamed_fields", vec!["SOME-FIELD"]),
+                ("tuple", vec!["0"]),
+                ("unit", vec![]),
+            ]
+        );
+        assert!(Variants::VARIANTS[0].fields[0].required);
+        assert!(!Variants::VARIANTS[2].fields[0].required);
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_features() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::openapi::schema::SchemaType;
+        use utoipa::openapi::{RefOr, Schema};
+        use utoipa::{PartialSchema, ToSchema};
+
+        #[serde_option(utoipa)]
+        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            patch_field: serde_option::Patch<u64>,
+        }
+
+        let schema = Example::schema();
+        let RefOr::T(Schema::Object(object)) = schema else {
+            panic!("schema should be an object")
+        };
+        let Some(RefOr::T(Schema::Object(nullable_field))) =
+            object.properties.get("nullable_field")
+        else {
+            panic!("nullable_field should exist and be an object")
         };
         let Some(RefOr::T(Schema::Object(not_required_field))) =
             object.properties.get("not_required_field")
@@ -160,23 +1555,733 @@ mod tests {
             panic!("not_required_field should exist and be an object")
         };
 
-        let SchemaType::Array(a) = &nullable_field.schema_type else {
-            panic!("nullable_field should be of array type")
+        let SchemaType::Array(a) = &nullable_field.schema_type else {
+            panic!("nullable_field should be of array type")
+        };
+        assert!(
+            a.contains(&utoipa::openapi::Type::Null),
+            "nullable_field should contain null in array type"
+        );
+
+        let SchemaType::Type(utoipa::openapi::Type::Integer) = &not_required_field.schema_type
+        else {
+            panic!("schema type should be integer")
+        };
+
+        let Some(RefOr::T(Schema::Object(patch_field))) = object.properties.get("patch_field")
+        else {
+            panic!("patch_field should exist and be an object")
+        };
+        let SchemaType::Array(a) = &patch_field.schema_type else {
+            panic!("patch_field should be of array type")
+        };
+        assert!(
+            a.contains(&utoipa::openapi::Type::Null),
+            "patch_field should contain null in array type"
+        );
+
+        assert_eq!(
+            &object.required,
+            &["nullable_field"],
+            "only nullable_field should be marked as required"
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_nullable_not_required() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::openapi::schema::SchemaType;
+        use utoipa::openapi::{RefOr, Schema, Type};
+        use utoipa::{PartialSchema, ToSchema};
+
+        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
+        struct Nested {
+            value: u64,
+        }
+
+        #[serde_option(utoipa)]
+        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<u64>>,
+            #[nullable]
+            #[not_required]
+            nested_field: Option<Option<Nested>>,
+        }
+
+        let RefOr::T(Schema::Object(object)) = Example::schema() else {
+            panic!("schema should be an object")
+        };
+        assert!(object.required.is_empty(), "no field should be required");
+
+        let Some(RefOr::T(Schema::Object(both_field))) = object.properties.get("both_field") else {
+            panic!("both_field should exist and be an object")
         };
-        assert!(
-            a.contains(&utoipa::openapi::Type::Null),
-            "nullable_field should contain null in array type"
+        let SchemaType::Array(types) = &both_field.schema_type else {
+            panic!("both_field should be of array type")
+        };
+        assert_eq!(
+            types,
+            &[Type::Integer, Type::Null],
+            "both_field should contain null exactly once"
         );
 
-        let SchemaType::Type(utoipa::openapi::Type::Integer) = &not_required_field.schema_type
+        let Some(RefOr::T(Schema::OneOf(nested_field))) = object.properties.get("nested_field")
         else {
-            panic!("schema type should be integer")
+            panic!("nested_field should exist and be a oneOf")
         };
+        let nulls = nested_field
+            .items
+            .iter()
+            .filter(|item| {
+                matches!(item, RefOr::T(Schema::Object(item))
+                    if item.schema_type == SchemaType::Type(Type::Null))
+            })
+            .count();
+        assert_eq!(nulls, 1, "nested_field should contain null exactly once");
+        assert_eq!(
+            nested_field.items.len(),
+            2,
+            "nested_field should only add null"
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_detection() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::openapi::{RefOr, Schema};
+        use utoipa::PartialSchema;
+
+        #[serde_option]
+        #[derive(Deserialize, Serialize, utoipa::ToSchema, PartialEq, Debug)]
+        struct Detected {
+            #[nullable]
+            nullable_field: Option<u64>,
+        }
+
+        #[serde_option(utoipa = false)]
+        #[derive(Deserialize, Serialize, utoipa::ToSchema, PartialEq, Debug)]
+        struct OptedOut {
+            #[nullable]
+            nullable_field: Option<u64>,
+        }
 
+        let RefOr::T(Schema::Object(detected)) = Detected::schema() else {
+            panic!("schema should be an object")
+        };
         assert_eq!(
-            &object.required,
+            &detected.required,
             &["nullable_field"],
+            "deriving `ToSchema` should enable the utoipa attributes"
+        );
+
+        let RefOr::T(Schema::Object(opted_out)) = OptedOut::schema() else {
+            panic!("schema should be an object")
+        };
+        assert!(
+            opted_out.required.is_empty(),
+            "`utoipa = false` should disable the utoipa attributes"
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_openapi_3_0() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::{PartialSchema, ToSchema};
+
+        #[serde_option(utoipa(openapi = "3.0"))]
+        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<String>>,
+            patch_field: serde_option::Patch<u64>,
+            #[nullable(as_default)]
+            #[not_required]
+            as_default_field: String,
+            #[not_required(accept_null)]
+            accept_null_field: Option<u64>,
+            unannotated_field: Option<u64>,
+        }
+
+        let schema = serde_json::to_value(Example::schema()).expect("Serialization should work");
+        let properties = &schema["properties"];
+        for field in [
+            "nullable_field",
+            "both_field",
+            "patch_field",
+            "as_default_field",
+            "accept_null_field",
+            "unannotated_field",
+        ] {
+            assert_eq!(
+                properties[field]["nullable"],
+                json!(true),
+                "{field} should be marked with `nullable: true`"
+            );
+            assert!(
+                properties[field]["type"].is_string(),
+                "{field} should not have `null` in its type"
+            );
+        }
+        assert_eq!(properties["not_required_field"]["nullable"], json!(null));
+        assert_eq!(
+            schema["required"],
+            json!(["nullable_field"]),
             "only nullable_field should be marked as required"
         );
     }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_default_fields() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::{PartialSchema, ToSchema};
+
+        #[serde_option(utoipa)]
+        #[derive(Deserialize, Serialize, ToSchema, PartialEq, Debug)]
+        struct Example {
+            #[serde(default = "default_fn")]
+            #[nullable]
+            nullable_default: Option<String>,
+            #[serde(default)]
+            #[nullable]
+            nullable_trait_default: Option<String>,
+            #[nullable]
+            nullable: Option<String>,
+        }
+        fn default_fn() -> Option<String> {
+            Some("hello".into())
+        }
+
+        let schema = serde_json::to_value(Example::schema()).expect("Serialization should work");
+        assert_eq!(
+            schema["required"],
+            json!(["nullable"]),
+            "fields with a default should not be required"
+        );
+        assert_eq!(
+            schema["properties"]["nullable_default"]["default"],
+            json!("hello"),
+            "the default value should be documented"
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_default_fields_openapi_3_0() {
+        use serde::{Deserialize, Serialize};
+        use utoipa::{IntoParams, PartialSchema, ToSchema};
+
+        #[serde_option(utoipa(openapi = "3.0"))]
+        #[derive(Deserialize, Serialize, ToSchema, IntoParams, PartialEq, Debug)]
+        #[into_params(parameter_in = Query)]
+        struct Example {
+            #[serde(default = "default_fn")]
+            #[nullable]
+            nullable_default: Option<String>,
+            #[serde(default)]
+            #[nullable]
+            nullable_trait_default: Option<String>,
+            #[nullable]
+            nullable: Option<String>,
+        }
+        fn default_fn() -> Option<String> {
+            Some("hello".into())
+        }
+
+        let schema = serde_json::to_value(Example::schema()).expect("Serialization should work");
+        assert_eq!(
+            schema["required"],
+            json!(["nullable"]),
+            "fields with a default should not be required"
+        );
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["nullable_default"]["default"],
+            json!("hello"),
+            "the default value should be documented"
+        );
+        for field in ["nullable_default", "nullable_trait_default", "nullable"] {
+            assert_eq!(
+                properties[field]["nullable"],
+                json!(true),
+                "{field} should be marked with `nullable: true`"
+            );
+        }
+
+        let params = Example::into_params(|| None)
+            .into_iter()
+            .map(|param| {
+                let param = serde_json::to_value(param).expect("Serialization should work");
+                (
+                    param["name"].clone(),
+                    param["required"].clone(),
+                    param["schema"]["nullable"].clone(),
+                    param["schema"]["default"].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                (
+                    json!("nullable_default"),
+                    json!(false),
+                    json!(true),
+                    json!("hello")
+                ),
+                (
+                    json!("nullable_trait_default"),
+                    json!(false),
+                    json!(true),
+                    json!(null)
+                ),
+                // `IntoParams` ignores `required` next to `schema_with`, so required
+                // parameters keep a `null` type instead of `nullable: true`
+                (json!("nullable"), json!(true), json!(null), json!(null)),
+            ],
+            "parameters with a default should not be required"
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_utoipa_into_params() {
+        use serde::Deserialize;
+        use utoipa::IntoParams;
+
+        #[serde_option]
+        #[derive(Deserialize, IntoParams, PartialEq, Debug)]
+        #[into_params(parameter_in = Query)]
+        struct Params {
+            #[nullable]
+            nullable_param: Option<u64>,
+            #[not_required]
+            not_required_param: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both_param: Option<Option<u64>>,
+            patch_param: serde_option::Patch<u64>,
+            #[nullable(as_default)]
+            as_default_param: u64,
+            #[not_required(accept_null)]
+            accept_null_param: Option<u64>,
+        }
+
+        let params = Params::into_params(|| None)
+            .into_iter()
+            .map(|param| {
+                let param = serde_json::to_value(param).expect("Serialization should work");
+                let nullable = param["schema"]["type"]
+                    .as_array()
+                    .is_some_and(|types| types.contains(&json!("null")));
+                (param["name"].clone(), param["required"].clone(), nullable)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                (json!("nullable_param"), json!(true), true),
+                (json!("not_required_param"), json!(false), false),
+                (json!("both_param"), json!(false), true),
+                (json!("patch_param"), json!(false), true),
+                (json!("as_default_param"), json!(true), true),
+                (json!("accept_null_param"), json!(false), true),
+            ],
+            "parameters should match the (de)serialization"
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_schemars_features() {
+        use schemars::{schema_for, JsonSchema};
+        use serde::{Deserialize, Serialize};
+
+        type Maybe<T> = Option<T>;
+
+        #[serde_option(schemars, option_aliases(Maybe))]
+        #[derive(Deserialize, Serialize, JsonSchema)]
+        struct Example {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<u64>>,
+            #[not_required]
+            alias_field: Maybe<u64>,
+            patch_field: serde_option::Patch<u64>,
+            #[nullable(as_default)]
+            as_default_field: u64,
+            #[not_required(accept_null)]
+            accept_null_field: Option<u64>,
+        }
+
+        let schema = schema_for!(Example);
+        let properties = &schema.as_value()["properties"];
+        let nullable = json!(["integer", "null"]);
+        assert_eq!(properties["nullable_field"]["type"], nullable);
+        assert_eq!(properties["not_required_field"]["type"], json!("integer"));
+        assert_eq!(properties["both_field"]["type"], nullable);
+        assert_eq!(properties["alias_field"]["type"], json!("integer"));
+        assert_eq!(properties["patch_field"]["type"], nullable);
+        assert_eq!(properties["as_default_field"]["type"], nullable);
+        assert_eq!(properties["accept_null_field"]["type"], nullable);
+        assert_eq!(
+            schema.as_value()["required"],
+            json!(["nullable_field", "as_default_field"]),
+            "only nullable_field and as_default_field should be marked as required"
+        );
+    }
+
+    #[cfg(feature = "json_schema")]
+    #[test]
+    fn test_json_schema() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(json_schema)]
+        #[derive(Deserialize, Serialize)]
+        #[serde(rename_all = "camelCase", deny_unknown_fields)]
+        struct Example<T> {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<String>,
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<u64>>,
+            #[not_required(assume_option)]
+            generic_field: T,
+            patch_field: serde_option::Patch<Nested>,
+            #[serde(skip)]
+            #[allow(dead_code)]
+            skipped_field: u64,
+        }
+
+        #[serde_option(json_schema)]
+        #[derive(Deserialize, Serialize)]
+        #[serde(tag = "kind")]
+        enum Nested {
+            Leaf {
+                #[nullable]
+                value: Option<bool>,
+            },
+            Branch {
+                children: Vec<Nested>,
+            },
+            Empty,
+        }
+
+        let schema = Example::<Option<u8>>::json_schema();
+        assert_eq!(
+            schema["$schema"],
+            json!("https://json-schema.org/draft/2020-12/schema")
+        );
+        assert_eq!(schema["additionalProperties"], json!(false));
+        assert_eq!(
+            schema["required"],
+            json!(["nullableField"]),
+            "only nullableField should be marked as required"
+        );
+        let properties = &schema["properties"];
+        let nullable = json!(["integer", "null"]);
+        assert_eq!(properties["nullableField"]["type"], nullable);
+        assert_eq!(properties["notRequiredField"], json!({"type": "string"}));
+        assert_eq!(properties["bothField"]["type"], nullable);
+        assert_eq!(properties["genericField"]["type"], json!("integer"));
+        assert!(properties.get("skippedField").is_none());
+
+        // Nested types are inlined, and recursive references point to `$defs`
+        let nested = &properties["patchField"]["anyOf"];
+        assert_eq!(nested[1], json!({"type": "null"}));
+        let variants = &nested[0]["oneOf"];
+        assert_eq!(
+            variants[0],
+            json!({
+                "type": "object",
+                "properties": {
+                    "kind": {"const": "Leaf"},
+                    "value": {"type": ["boolean", "null"]},
+                },
+                "required": ["kind", "value"],
+            })
+        );
+        assert_eq!(
+            variants[1]["properties"]["children"]["items"],
+            json!({"$ref": "#/$defs/Nested"})
+        );
+        assert_eq!(variants[2]["required"], json!(["kind"]));
+        assert_eq!(schema["$defs"]["Nested"], nested[0]);
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn test_validate_presence() {
+        use serde::Deserialize;
+        use validate::{PresenceError, PresenceErrorKind::*};
+
+        // The fields are only read through the validation
+        #[allow(dead_code)]
+        #[serde_option(validate)]
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Example<T> {
+            #[nullable]
+            nullable_field: Option<u64>,
+            #[not_required]
+            not_required_field: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both_field: Option<Option<u64>>,
+            plain_field: u64,
+            json_field: serde_json::Value,
+            custom_field: Custom,
+            nested_field: Option<Vec<Nested>>,
+            generic_field: Option<T>,
+        }
+
+        // Types without `ValidatePresence` are not checked, since they might accept `null`
+        #[allow(dead_code)]
+        #[derive(Deserialize)]
+        struct Custom(Option<u64>);
+
+        #[allow(dead_code)]
+        #[serde_option(validate)]
+        #[derive(Deserialize)]
+        #[serde(tag = "kind", rename_all = "snake_case")]
+        enum Nested {
+            Leaf {
+                #[nullable]
+                value: Option<bool>,
+            },
+            Branch(Box<Nested>),
+            Empty,
+        }
+
+        let valid = json!({
+            "nullableField": null,
+            "bothField": null,
+            "plainField": 1,
+            "jsonField": null,
+            "customField": null,
+            "nestedField": [{"kind": "leaf", "value": null}, {"kind": "empty"}],
+        });
+        assert_eq!(Example::<u64>::validate_presence(&valid), Ok(()));
+        serde_json::from_value::<Example<u64>>(valid).expect("Validated value should deserialize");
+
+        let invalid = json!({
+            "notRequiredField": null,
+            "plainField": null,
+            "jsonField": null,
+            "customField": null,
+            "nestedField": [
+                {"kind": "leaf"},
+                {"kind": "branch", "value": false},
+                {"kind": "unknown"},
+            ],
+        });
+        assert_eq!(
+            Example::<u64>::validate_presence(&invalid),
+            Err(vec![
+                PresenceError::new("/nullableField", MissingRequiredField),
+                PresenceError::new("/notRequiredField", NullNotAllowed),
+                PresenceError::new("/plainField", NullNotAllowed),
+                PresenceError::new("/nestedField/0/value", MissingRequiredField),
+            ]),
+            "All violations should be reported"
+        );
+
+        #[allow(dead_code)]
+        #[serde_option(validate)]
+        #[derive(Deserialize, Debug)]
+        struct Renamed {
+            #[nullable]
+            #[serde(alias = "bb")]
+            b: Option<u64>,
+            #[not_required]
+            #[serde(rename(serialize = "out", deserialize = "in"))]
+            renamed: Option<u64>,
+        }
+
+        let aliased = json!({"bb": 1});
+        assert_eq!(Renamed::validate_presence(&aliased), Ok(()));
+        serde_json::from_value::<Renamed>(aliased).expect("Validated value should deserialize");
+        let invalid = json!({"in": null, "out": 1});
+        assert_eq!(
+            Renamed::validate_presence(&invalid),
+            Err(vec![
+                PresenceError::new("/b", MissingRequiredField),
+                PresenceError::new("/in", NullNotAllowed),
+            ]),
+            "Fields should be validated by their deserialized names"
+        );
+        let error = serde_json::from_value::<Renamed>(json!({"b": 1, "in": null}))
+            .expect_err("Should not accept null for `in`");
+        assert_eq!(error.to_string(), "field `/in` must not be null");
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml() {
+        use serde::{Deserialize, Serialize};
+
+        #[serde_option(xml)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable]
+            nullable: Option<u64>,
+            #[not_required]
+            optional: Option<u64>,
+            #[nullable]
+            #[not_required]
+            both: Option<Option<u64>>,
+            patch: serde_option::Patch<u64>,
+        }
+
+        let nil = r#"xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:nil="true""#;
+        let cases = [
+            (
+                Example {
+                    nullable: Some(1),
+                    optional: Some(2),
+                    both: Some(Some(3)),
+                    patch: Patch::Value(4),
+                },
+                "<Example><nullable>1</nullable><optional>2</optional><both>3</both>\
+                <patch>4</patch></Example>"
+                    .to_owned(),
+            ),
+            (
+                Example {
+                    nullable: None,
+                    optional: None,
+                    both: Some(None),
+                    patch: Patch::Null,
+                },
+                format!("<Example><nullable {nil}/><both {nil}/><patch {nil}/></Example>"),
+            ),
+            (
+                Example {
+                    nullable: None,
+                    optional: None,
+                    both: None,
+                    patch: Patch::Absent,
+                },
+                format!("<Example><nullable {nil}/></Example>"),
+            ),
+        ];
+        for (model, expected) in &cases {
+            let serialized = quick_xml::se::to_string(model).expect("Serialization should work");
+            assert_eq!(&serialized, expected);
+            assert_eq!(
+                model,
+                &quick_xml::de::from_str::<Example>(&serialized).expect("Roundtrip should work"),
+                "Roundtrip of {serialized} should be equal"
+            );
+        }
+
+        // The namespace is usually declared on the root element
+        let root = r#"<Example xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+            <nullable xsi:nil="true"/><both xsi:nil="true"/></Example>"#;
+        assert_eq!(
+            quick_xml::de::from_str::<Example>(root).expect("Deserialization should work"),
+            Example {
+                nullable: None,
+                optional: None,
+                both: Some(None),
+                patch: Patch::Absent,
+            }
+        );
+        assert!(
+            quick_xml::de::from_str::<Example>("<Example/>").is_err(),
+            "Nullable fields should still be required"
+        );
+        let optional_nil = format!("<Example><nullable {nil}/><optional {nil}/></Example>");
+        assert!(
+            quick_xml::de::from_str::<Example>(&optional_nil).is_err(),
+            "Fields which aren't nullable should reject `xsi:nil`"
+        );
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml_sentinels() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Id(i64);
+
+        #[serde_option(xml)]
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Example {
+            #[nullable(sentinel = -1, sentinel_out)]
+            count: Option<u64>,
+            #[nullable(sentinel = "N/A", sentinel_out)]
+            name: Option<String>,
+            #[nullable(sentinel = 0, sentinel_out)]
+            id: Option<Id>,
+        }
+
+        let cases = [
+            (
+                Example {
+                    count: Some(5),
+                    name: Some("name".to_owned()),
+                    id: Some(Id(7)),
+                },
+                "<Example><count>5</count><name>name</name><id>7</id></Example>",
+            ),
+            (
+                Example {
+                    count: None,
+                    name: None,
+                    id: None,
+                },
+                "<Example><count>-1</count><name>N/A</name><id>0</id></Example>",
+            ),
+        ];
+        for (model, expected) in &cases {
+            let serialized = quick_xml::se::to_string(model).expect("Serialization should work");
+            assert_eq!(&serialized, expected);
+            assert_eq!(
+                quick_xml::de::from_str::<Example>(&serialized)
+                    .as_ref()
+                    .map_err(ToString::to_string),
+                Ok(model),
+                "Roundtrip of {serialized} should be equal"
+            );
+        }
+
+        let nil = r#"xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:nil="true""#;
+        assert_eq!(
+            quick_xml::de::from_str::<Example>(&format!(
+                "<Example><count {nil}/><name>N/A</name><id>-0.0</id></Example>"
+            ))
+            .map_err(|err| err.to_string()),
+            Ok(Example {
+                count: None,
+                name: None,
+                id: None,
+            }),
+            "`xsi:nil` and numbers equal to the sentinel should be `None`"
+        );
+        assert!(
+            quick_xml::de::from_str::<Example>(
+                "<Example><count>many</count><name>N/A</name><id>0</id></Example>"
+            )
+            .is_err(),
+            "Other text should be parsed as the value"
+        );
+    }
 }
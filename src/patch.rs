@@ -0,0 +1,175 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A field value that is either absent, explicitly `null`, or holds a value.
+///
+/// This is a readable alternative to `Option<Option<T>>` for fields that are both nullable
+/// and not required, which is common in `PATCH`-style APIs where omitting a field means
+/// "leave unchanged" and sending `null` means "clear the value".
+///
+/// When used as a field type inside a [`macro@crate::serde_option`] struct or enum, the macro
+/// automatically adds `#[serde(default, skip_serializing_if = "...")]`, so that
+/// [`Patch::Absent`] is produced for missing fields and omitted again on serialization. The
+/// type is recognized as `serde_option::Patch`, or by the names listed in `patch_aliases(...)`.
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// use serde_option::{serde_option, Patch};
+///
+/// #[serde_option(patch_aliases(Patch))]
+/// #[derive(Deserialize, Serialize)]
+/// struct UpdateUser {
+///     nickname: Patch<String>,
+/// }
+///
+/// let update: UpdateUser = serde_json::from_str(r#"{"nickname": null}"#).unwrap();
+/// assert_eq!(update.nickname, Patch::Null);
+///
+/// let update: UpdateUser = serde_json::from_str("{}").unwrap();
+/// assert_eq!(update.nickname, Patch::Absent);
+/// assert_eq!(serde_json::to_string(&update).unwrap(), "{}");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Patch<T> {
+    /// The field was not present
+    #[default]
+    Absent,
+    /// The field was present and `null`
+    Null,
+    /// The field was present and held a value
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Returns `true` if the field was not present
+    pub const fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// Returns `true` if the field was present and `null`
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Patch::Null)
+    }
+
+    /// Returns `true` if the field was present and held a value
+    pub const fn is_value(&self) -> bool {
+        matches!(self, Patch::Value(_))
+    }
+
+    /// Converts from `&Patch<T>` to `Patch<&T>`
+    pub const fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(value),
+        }
+    }
+
+    /// Converts from `&mut Patch<T>` to `Patch<&mut T>`
+    pub fn as_mut(&mut self) -> Patch<&mut T> {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(value),
+        }
+    }
+
+    /// Maps a `Patch<T>` to `Patch<U>` by applying a function to a contained value
+    pub fn map<U, F>(self, f: F) -> Patch<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(f(value)),
+        }
+    }
+
+    /// Returns the contained value, discarding the difference between
+    /// [`Patch::Absent`] and [`Patch::Null`]
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Patch::Absent | Patch::Null => None,
+            Patch::Value(value) => Some(value),
+        }
+    }
+
+    /// Applies the patch to `target`:
+    ///
+    /// * [`Patch::Absent`] leaves `target` unchanged
+    /// * [`Patch::Null`] sets `target` to `None`
+    /// * [`Patch::Value`] sets `target` to `Some(value)`
+    ///
+    /// ```
+    /// use serde_option::Patch;
+    ///
+    /// let mut nickname = Some("old".to_string());
+    /// Patch::Absent.apply_to(&mut nickname);
+    /// assert_eq!(nickname.as_deref(), Some("old"));
+    /// Patch::Value("new".to_string()).apply_to(&mut nickname);
+    /// assert_eq!(nickname.as_deref(), Some("new"));
+    /// Patch::Null.apply_to(&mut nickname);
+    /// assert_eq!(nickname, None);
+    /// ```
+    pub fn apply_to(self, target: &mut Option<T>) {
+        match self {
+            Patch::Absent => {}
+            Patch::Null => *target = None,
+            Patch::Value(value) => *target = Some(value),
+        }
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Patch<T> {
+    fn from(value: Option<Option<T>>) -> Self {
+        match value {
+            None => Patch::Absent,
+            Some(None) => Patch::Null,
+            Some(Some(value)) => Patch::Value(value),
+        }
+    }
+}
+
+impl<T> From<Patch<T>> for Option<Option<T>> {
+    fn from(value: Patch<T>) -> Self {
+        match value {
+            Patch::Absent => None,
+            Patch::Null => Some(None),
+            Patch::Value(value) => Some(Some(value)),
+        }
+    }
+}
+
+impl<T> Serialize for Patch<T>
+where
+    T: Serialize,
+{
+    /// [`Patch::Absent`] should be skipped with `skip_serializing_if`, otherwise it is
+    /// serialized the same way as [`Patch::Null`]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Patch::Absent | Patch::Null => serializer.serialize_none(),
+            Patch::Value(value) => serializer.serialize_some(value),
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    /// Missing fields are handled through `#[serde(default)]`, so only [`Patch::Null`] and
+    /// [`Patch::Value`] can be produced here
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::deserialize(deserializer).map(|value| match value {
+            None => Patch::Null,
+            Some(value) => Patch::Value(value),
+        })
+    }
+}
@@ -0,0 +1,1585 @@
+//! Helpers referenced by the code generated by the [`macro@crate::serde_option`] macro.
+//!
+//! This module is not part of the public API and may change without notice. It exists so that
+//! the macro expansion only depends on the `serde_option` crate, instead of requiring users to
+//! add extra dependencies of their own.
+//!
+//! The helpers are adapted from the `serde_with` library:
+//! <https://docs.rs/serde_with/3.15.1/src/serde_with/rust.rs.html>
+//! which is licensed under the MIT license.
+//!
+//! Copyright (c) 2015
+//! Permission is hereby granted, free of charge, to any
+//! person obtaining a copy of this software and associated
+//! documentation files (the "Software"), to deal in the
+//! Software without restriction, including without
+//! limitation the rights to use, copy, modify, merge,
+//! publish, distribute, sublicense, and/or sell copies of
+//! the Software, and to permit persons to whom the Software
+//! is furnished to do so, subject to the following
+//! conditions:
+//!
+//! The above copyright notice and this permission notice
+//! shall be included in all copies or substantial portions
+//! of the Software.
+//!
+//! THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+//! ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+//! TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+//! PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+//! SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+//! CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+//! OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+//! IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+//! DEALINGS IN THE SOFTWARE.
+
+/// Used in the `#[serde(bound(...))]` attributes generated for fields of generic types
+pub use serde;
+
+/// Implemented by [`Option`], so that the helpers can be used on fields whose type is only
+/// assumed to be an `Option`, e.g. type aliases and generic parameters
+pub trait OptionLike: Sized {
+    /// The `T` in `Option<T>`
+    type Inner;
+
+    /// Convert from an `Option`
+    fn from_option(option: Option<Self::Inner>) -> Self;
+
+    /// Convert to an `Option` of a reference
+    fn as_option(&self) -> Option<&Self::Inner>;
+}
+
+impl<T> OptionLike for Option<T> {
+    type Inner = T;
+
+    fn from_option(option: Option<T>) -> Self {
+        option
+    }
+
+    fn as_option(&self) -> Option<&T> {
+        self.as_ref()
+    }
+}
+
+/// Equivalent of `Option::is_none` for use in `skip_serializing_if`
+pub fn is_none<O: OptionLike>(option: &O) -> bool {
+    option.as_option().is_none()
+}
+
+/// Whether a value is equal to its default, for use in `skip_serializing_if`
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Equivalent of `#[serde(with = "Option")]`, which disables serde's implicit default for
+/// missing `Option` fields
+pub mod nullable {
+    use super::OptionLike;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Deserialize an optional value
+    pub fn deserialize<'de, D, O>(deserializer: D) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        Option::deserialize(deserializer).map(O::from_option)
+    }
+
+    /// Serialize an optional value
+    pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        O: OptionLike,
+        O::Inner: Serialize,
+        S: Serializer,
+    {
+        option.as_option().serialize(serializer)
+    }
+}
+
+/// Deserialize `null` as the default value, used for fields which aren't an `Option`, but accept
+/// `null`.
+pub mod null_as_default {
+    use serde::{Deserialize, Deserializer};
+
+    /// Deserialize a value, or its default for `null`
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + Default,
+    {
+        Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+    }
+}
+
+/// Serialize an `Option<T>` as the bare `T`, used for fields which may be absent, but not `null`.
+///
+/// `None` values are expected to be skipped with `skip_serializing_if`.
+pub mod unwrap_or_skip {
+    use super::OptionLike;
+    use crate::error::pointer;
+    use crate::{Error, ErrorKind};
+    use core::{fmt, marker::PhantomData};
+    use serde::de::{self, value::UnitDeserializer, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Deserialize value wrapped in `Some(T)`
+    pub fn deserialize<'de, D, O>(deserializer: D) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        O::Inner::deserialize(deserializer).map(|value| O::from_option(Some(value)))
+    }
+
+    /// Like [`deserialize`], but deserializes `null` as `None`, like a missing value
+    pub fn deserialize_or_null<'de, D, O>(deserializer: D) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        Option::deserialize(deserializer).map(O::from_option)
+    }
+
+    /// Like [`deserialize`], but reports `null` as [`ErrorKind::NullNotAllowed`] for `field`,
+    /// instead of the error of `T`. Types which accept `null` themselves, such as
+    /// `serde_json::Value` or `()`, are deserialized from it as usual.
+    pub fn deserialize_field<'de, D, O>(deserializer: D, field: &str) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        deserializer.deserialize_option(NonNullVisitor {
+            field,
+            marker: PhantomData,
+        })
+    }
+
+    /// Deserializes the inner value of an `Option`, rejecting `null` unless the inner type
+    /// accepts it
+    struct NonNullVisitor<'a, O> {
+        field: &'a str,
+        marker: PhantomData<O>,
+    }
+
+    impl<'de, O> Visitor<'de> for NonNullVisitor<'_, O>
+    where
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        type Value = O;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a value which is not null")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<O, E> {
+            O::Inner::deserialize(UnitDeserializer::new())
+                .map(|value| O::from_option(Some(value)))
+                .map_err(|_: E| {
+                    E::custom(Error::new(
+                        pointer("", self.field),
+                        ErrorKind::NullNotAllowed,
+                    ))
+                })
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<O, E> {
+            self.visit_none()
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<O, D::Error> {
+            deserialize(deserializer)
+        }
+    }
+
+    /// Serialize value if `Some(T)`, unit if `None`
+    pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        O: OptionLike,
+        O::Inner: Serialize,
+        S: Serializer,
+    {
+        if let Some(value) = option.as_option() {
+            value.serialize(serializer)
+        } else {
+            ().serialize(serializer)
+        }
+    }
+}
+
+/// Distinguish between a missing field (`None`), a `null` value (`Some(None)`) and an existing
+/// value (`Some(Some(T))`).
+///
+/// `None` values are expected to be skipped with `skip_serializing_if`, and missing fields to be
+/// filled in with `default`.
+pub mod double_option {
+    use super::OptionLike;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Deserialize potentially non-existing optional value
+    pub fn deserialize<'de, O, D>(deserializer: D) -> Result<O, D::Error>
+    where
+        O: OptionLike,
+        O::Inner: OptionLike + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        O::Inner::deserialize(deserializer).map(|value| O::from_option(Some(value)))
+    }
+
+    /// Serialize optional value, the inner `Option` is serialized as-is
+    pub fn serialize<S, O>(values: &O, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        O: OptionLike,
+        O::Inner: OptionLike + Serialize,
+    {
+        match values.as_option() {
+            None => serializer.serialize_unit(),
+            Some(inner) => inner.serialize(serializer),
+        }
+    }
+}
+
+/// Treat a sentinel value, such as `"N/A"` or `-1`, as `null`, used for
+/// `#[nullable(sentinel = ...)]`.
+///
+/// The inner value is deserialized through a wrapper which checks the primitive values passed
+/// to its visitor, so that the sentinel doesn't need to be of the same type as the value. This
+/// includes sequences, maps, structs and enums, e.g. `"N/A"` for a `Vec<String>`.
+pub mod sentinel {
+    use super::{form::FormValue, OptionLike};
+    use core::{cell::Cell, fmt, marker::PhantomData};
+    use serde::de::{self, value, EnumAccess, MapAccess, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+
+    /// The literal given with `sentinel = ...`
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Sentinel {
+        Str(&'static str),
+        Int(i128),
+        Float(f64),
+        Bool(bool),
+    }
+
+    impl Serialize for Sentinel {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match *self {
+                Sentinel::Str(value) => serializer.serialize_str(value),
+                Sentinel::Int(value) => match i64::try_from(value) {
+                    Ok(value) => serializer.serialize_i64(value),
+                    Err(_) => serializer.serialize_i128(value),
+                },
+                Sentinel::Float(value) => serializer.serialize_f64(value),
+                Sentinel::Bool(value) => serializer.serialize_bool(value),
+            }
+        }
+    }
+
+    /// Deserialize an optional value, where `null` and `sentinel` are `None`
+    pub fn deserialize<'de, D, O>(deserializer: D, sentinel: Sentinel) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        deserializer.deserialize_option(OptionVisitor {
+            sentinel,
+            text: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// Serialize an optional value, writing `sentinel` instead of `null` for `None`
+    pub fn serialize<O, S>(option: &O, serializer: S, sentinel: Sentinel) -> Result<S::Ok, S::Error>
+    where
+        O: OptionLike,
+        O::Inner: Serialize,
+        S: Serializer,
+    {
+        match option.as_option() {
+            None => sentinel.serialize(serializer),
+            option => option.serialize(serializer),
+        }
+    }
+
+    /// The XML equivalent of [`deserialize`] and [`serialize`], where the text of primitive
+    /// values is compared with the sentinel, and then parsed like form values. `quick-xml`
+    /// describes elements as maps, so they can't be read with `deserialize_any`.
+    #[cfg(feature = "xml")]
+    pub mod xml {
+        use super::{OptionLike, OptionVisitor, Sentinel};
+        use core::marker::PhantomData;
+        use serde::{Deserialize, Deserializer};
+
+        pub use super::serialize;
+
+        /// Deserialize an optional value, where `null` and the text `sentinel` are `None`
+        pub fn deserialize<'de, D, O>(deserializer: D, sentinel: Sentinel) -> Result<O, D::Error>
+        where
+            D: Deserializer<'de>,
+            O: OptionLike,
+            O::Inner: Deserialize<'de>,
+        {
+            deserializer.deserialize_option(OptionVisitor {
+                sentinel,
+                text: true,
+                marker: PhantomData,
+            })
+        }
+    }
+
+    /// The sentinel equivalent of [`super::double_option`], used for
+    /// `#[serde_option(null_repr = "...")]`, where the sentinel is `Some(None)`
+    pub mod double_option {
+        use super::{OptionLike, Sentinel};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a value which is present, where `null` and `sentinel` are `Some(None)`
+        pub fn deserialize<'de, D, O>(deserializer: D, sentinel: Sentinel) -> Result<O, D::Error>
+        where
+            D: Deserializer<'de>,
+            O: OptionLike,
+            O::Inner: OptionLike,
+            <O::Inner as OptionLike>::Inner: Deserialize<'de>,
+        {
+            super::deserialize(deserializer, sentinel).map(|inner| O::from_option(Some(inner)))
+        }
+
+        /// Serialize the inner `Option`, writing `sentinel` for `None`. The outer `None` is
+        /// expected to be skipped with `skip_serializing_if`.
+        pub fn serialize<O, S>(
+            option: &O,
+            serializer: S,
+            sentinel: Sentinel,
+        ) -> Result<S::Ok, S::Error>
+        where
+            O: OptionLike,
+            O::Inner: OptionLike,
+            <O::Inner as OptionLike>::Inner: Serialize,
+            S: Serializer,
+        {
+            match option.as_option() {
+                Some(inner) => super::serialize(inner, serializer, sentinel),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+
+    /// The sentinel equivalent of the `Patch` implementations, used for
+    /// `#[serde_option(null_repr = "...")]`, where the sentinel is `Patch::Null`
+    pub mod patch {
+        use super::Sentinel;
+        use crate::Patch;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a value which is present, where `null` and `sentinel` are `Patch::Null`
+        pub fn deserialize<'de, D, T>(
+            deserializer: D,
+            sentinel: Sentinel,
+        ) -> Result<Patch<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+        {
+            super::deserialize(deserializer, sentinel).map(|value| Patch::from(Some(value)))
+        }
+
+        /// Serialize `Patch::Null` as `sentinel`. `Patch::Absent` is expected to be skipped with
+        /// `skip_serializing_if`.
+        pub fn serialize<T, S>(
+            patch: &Patch<T>,
+            serializer: S,
+            sentinel: Sentinel,
+        ) -> Result<S::Ok, S::Error>
+        where
+            T: Serialize,
+            S: Serializer,
+        {
+            super::serialize(&patch.as_ref().into_option(), serializer, sentinel)
+        }
+    }
+
+    struct OptionVisitor<O> {
+        sentinel: Sentinel,
+        /// Whether primitive values are compared by their text, see [`xml`]
+        text: bool,
+        marker: PhantomData<O>,
+    }
+
+    impl<'de, O> Visitor<'de> for OptionVisitor<O>
+    where
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        type Value = O;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an optional value")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<O, D::Error> {
+            let matched = Cell::new(false);
+            let deserializer = SentinelDeserializer {
+                deserializer,
+                sentinel: self.sentinel,
+                text: self.text,
+                matched: &matched,
+            };
+            match O::Inner::deserialize(deserializer) {
+                Ok(value) => Ok(O::from_option(Some(value))),
+                // The error was raised by `SentinelVisitor` to stop deserializing
+                Err(_) if matched.get() => Ok(O::from_option(None)),
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    /// Wraps the visitors passed to `deserializer` in a [`SentinelVisitor`]
+    struct SentinelDeserializer<'a, D> {
+        deserializer: D,
+        sentinel: Sentinel,
+        text: bool,
+        matched: &'a Cell<bool>,
+    }
+
+    impl<'a, D> SentinelDeserializer<'a, D> {
+        fn wrap<V>(&self, visitor: V) -> SentinelVisitor<'a, V> {
+            SentinelVisitor {
+                visitor,
+                sentinel: self.sentinel,
+                text: self.text,
+                matched: self.matched,
+                is_enum: false,
+            }
+        }
+
+        /// Reads the text of a primitive value, unless it is the sentinel
+        fn text_value<'de>(self) -> Result<FormValue<'de, D::Error>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let text = String::deserialize(self.deserializer)?;
+            if self.sentinel.is_text(&text) {
+                self.matched.set(true);
+                return Err(de::Error::custom("sentinel value"));
+            }
+            Ok(FormValue {
+                text: Cow::Owned(text),
+                marker: PhantomData,
+            })
+        }
+    }
+
+    macro_rules! forward_deserialize {
+        ($($method:ident($($arg:ident: $type_:ty),*) => $forward:ident,)*) => {
+            $(
+                fn $method<V: Visitor<'de>>(
+                    self,
+                    $($arg: $type_,)*
+                    visitor: V,
+                ) -> Result<V::Value, D::Error> {
+                    let visitor = self.wrap(visitor);
+                    self.deserializer.$forward($($arg,)* visitor)
+                }
+            )*
+        };
+    }
+
+    /// Forwards primitives to `deserialize_any`, or reads them from the text of the value
+    macro_rules! forward_primitive {
+        ($($method:ident,)*) => {
+            $(
+                fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+                    if self.text {
+                        return self.text_value()?.$method(visitor);
+                    }
+                    let visitor = self.wrap(visitor);
+                    self.deserializer.deserialize_any(visitor)
+                }
+            )*
+        };
+    }
+
+    /// Forwards compound values to `deserialize_any`, or to the same method for text, where
+    /// only primitive values are compared with the sentinel
+    macro_rules! forward_compound {
+        ($($method:ident($($arg:ident: $type_:ty),*),)*) => {
+            $(
+                fn $method<V: Visitor<'de>>(
+                    self,
+                    $($arg: $type_,)*
+                    visitor: V,
+                ) -> Result<V::Value, D::Error> {
+                    let visitor = self.wrap(visitor);
+                    if self.text {
+                        self.deserializer.$method($($arg,)* visitor)
+                    } else {
+                        $(let _ = $arg;)*
+                        self.deserializer.deserialize_any(visitor)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl<'de, D: Deserializer<'de>> Deserializer<'de> for SentinelDeserializer<'_, D> {
+        type Error = D::Error;
+
+        // Values are deserialized with `deserialize_any`, since the sentinel may be of a
+        // different type, e.g. `"N/A"` for a number. The visitor of the value still rejects
+        // other values of the wrong type.
+        forward_primitive! {
+            deserialize_bool,
+            deserialize_i8,
+            deserialize_i16,
+            deserialize_i32,
+            deserialize_i64,
+            deserialize_i128,
+            deserialize_u8,
+            deserialize_u16,
+            deserialize_u32,
+            deserialize_u64,
+            deserialize_u128,
+            deserialize_f32,
+            deserialize_f64,
+            deserialize_char,
+            deserialize_str,
+            deserialize_string,
+        }
+
+        forward_compound! {
+            deserialize_seq(),
+            deserialize_tuple(len: usize),
+            deserialize_tuple_struct(name: &'static str, len: usize),
+            deserialize_map(),
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        }
+
+        forward_deserialize! {
+            deserialize_any() => deserialize_any,
+            deserialize_bytes() => deserialize_bytes,
+            deserialize_byte_buf() => deserialize_byte_buf,
+            deserialize_option() => deserialize_option,
+            deserialize_unit() => deserialize_unit,
+            deserialize_unit_struct(name: &'static str) => deserialize_unit_struct,
+            deserialize_newtype_struct(name: &'static str) => deserialize_newtype_struct,
+            deserialize_identifier() => deserialize_identifier,
+            deserialize_ignored_any() => deserialize_ignored_any,
+        }
+
+        // Self-describing formats write unit variants as strings, and other variants as maps
+        // with a single entry, which are passed to the visitor of the enum as such
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, D::Error> {
+            if self.text {
+                let visitor = self.wrap(visitor);
+                return self.deserializer.deserialize_enum(name, variants, visitor);
+            }
+            let visitor = SentinelVisitor {
+                is_enum: true,
+                ..self.wrap(visitor)
+            };
+            self.deserializer.deserialize_any(visitor)
+        }
+
+        fn is_human_readable(&self) -> bool {
+            self.deserializer.is_human_readable()
+        }
+    }
+
+    /// Forwards to `visitor`, unless it is given the sentinel. In that case, `matched` is set
+    /// and an error is returned, since there is no value of the visitor's type to return.
+    struct SentinelVisitor<'a, V> {
+        visitor: V,
+        sentinel: Sentinel,
+        text: bool,
+        matched: &'a Cell<bool>,
+        /// Whether `visitor` expects an enum, which is then given strings and maps as variants
+        is_enum: bool,
+    }
+
+    impl<V> SentinelVisitor<'_, V> {
+        fn visitor<E: de::Error>(self, is_sentinel: bool) -> Result<V, E> {
+            if is_sentinel {
+                self.matched.set(true);
+                Err(E::custom("sentinel value"))
+            } else {
+                Ok(self.visitor)
+            }
+        }
+    }
+
+    impl Sentinel {
+        fn is_bool(&self, value: &bool) -> bool {
+            *self == Sentinel::Bool(*value)
+        }
+
+        // Numbers are compared by value, since formats differ in how they write them, e.g.
+        // `-1.0` may be read as an integer, and `-1` as a float
+
+        fn is_int<T: Copy + TryInto<i128>>(&self, value: &T) -> bool {
+            (*value).try_into().is_ok_and(|value| match *self {
+                Sentinel::Int(sentinel) => sentinel == value,
+                Sentinel::Float(sentinel) => float_eq_int(sentinel, value),
+                _ => false,
+            })
+        }
+
+        fn is_float<T: Copy + Into<f64>>(&self, value: &T) -> bool {
+            let value = (*value).into();
+            match *self {
+                Sentinel::Int(sentinel) => float_eq_int(value, sentinel),
+                Sentinel::Float(sentinel) => sentinel == value,
+                _ => false,
+            }
+        }
+
+        fn is_str(&self, value: &str) -> bool {
+            matches!(*self, Sentinel::Str(sentinel) if sentinel == value)
+        }
+
+        /// Whether `text` is the sentinel, or a number or boolean equal to it
+        fn is_text(&self, text: &str) -> bool {
+            match *self {
+                Sentinel::Str(sentinel) => sentinel == text,
+                Sentinel::Bool(sentinel) => text.parse() == Ok(sentinel),
+                Sentinel::Int(_) | Sentinel::Float(_) => {
+                    text.parse::<i128>().is_ok_and(|value| self.is_int(&value))
+                        || text.parse::<f64>().is_ok_and(|value| self.is_float(&value))
+                }
+            }
+        }
+
+        fn never<T: ?Sized>(&self, _: &T) -> bool {
+            false
+        }
+    }
+
+    /// Whether `float` is integral and equal to `int`, without rounding either of them
+    fn float_eq_int(float: f64, int: i128) -> bool {
+        float.fract() == 0.0 && float as i128 == int && int as f64 == float
+    }
+
+    macro_rules! forward_visit {
+        ($($method:ident($type_:ty) => $is_sentinel:path,)*) => {
+            $(
+                fn $method<E: de::Error>(self, value: $type_) -> Result<V::Value, E> {
+                    let is_sentinel = $is_sentinel(&self.sentinel, &value);
+                    self.visitor(is_sentinel)?.$method(value)
+                }
+            )*
+        };
+    }
+
+    impl<'de, V: Visitor<'de>> Visitor<'de> for SentinelVisitor<'_, V> {
+        type Value = V::Value;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            self.visitor.expecting(formatter)
+        }
+
+        forward_visit! {
+            visit_bool(bool) => Sentinel::is_bool,
+            visit_i8(i8) => Sentinel::is_int,
+            visit_i16(i16) => Sentinel::is_int,
+            visit_i32(i32) => Sentinel::is_int,
+            visit_i64(i64) => Sentinel::is_int,
+            visit_i128(i128) => Sentinel::is_int,
+            visit_u8(u8) => Sentinel::is_int,
+            visit_u16(u16) => Sentinel::is_int,
+            visit_u32(u32) => Sentinel::is_int,
+            visit_u64(u64) => Sentinel::is_int,
+            visit_u128(u128) => Sentinel::is_int,
+            visit_f32(f32) => Sentinel::is_float,
+            visit_f64(f64) => Sentinel::is_float,
+            visit_char(char) => Sentinel::never,
+            visit_bytes(&[u8]) => Sentinel::never,
+            visit_borrowed_bytes(&'de [u8]) => Sentinel::never,
+            visit_byte_buf(Vec<u8>) => Sentinel::never,
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<V::Value, E> {
+            let is_sentinel = self.sentinel.is_str(value);
+            let is_enum = self.is_enum;
+            let visitor = self.visitor(is_sentinel)?;
+            if is_enum {
+                visitor.visit_enum(value::StrDeserializer::new(value))
+            } else {
+                visitor.visit_str(value)
+            }
+        }
+
+        fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<V::Value, E> {
+            let is_sentinel = self.sentinel.is_str(value);
+            let is_enum = self.is_enum;
+            let visitor = self.visitor(is_sentinel)?;
+            if is_enum {
+                visitor.visit_enum(value::BorrowedStrDeserializer::new(value))
+            } else {
+                visitor.visit_borrowed_str(value)
+            }
+        }
+
+        fn visit_string<E: de::Error>(self, value: String) -> Result<V::Value, E> {
+            let is_sentinel = self.sentinel.is_str(&value);
+            let is_enum = self.is_enum;
+            let visitor = self.visitor(is_sentinel)?;
+            if is_enum {
+                visitor.visit_enum(value::StringDeserializer::new(value))
+            } else {
+                visitor.visit_string(value)
+            }
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+            self.visitor.visit_none()
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+            self.visitor.visit_unit()
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+            self.visitor.visit_some(deserializer)
+        }
+
+        // Newtypes are checked as well, e.g. `struct Id(u64)` with `sentinel = 0`
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<V::Value, D::Error> {
+            let deserializer = SentinelDeserializer {
+                deserializer,
+                sentinel: self.sentinel,
+                text: self.text,
+                matched: self.matched,
+            };
+            self.visitor.visit_newtype_struct(deserializer)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+            self.visitor.visit_seq(seq)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+            if self.is_enum {
+                self.visitor
+                    .visit_enum(value::MapAccessDeserializer::new(map))
+            } else {
+                self.visitor.visit_map(map)
+            }
+        }
+
+        fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+            self.visitor.visit_enum(data)
+        }
+    }
+}
+
+/// Form and query string semantics for `#[serde_option(form)]`, where there is no `null`, and an
+/// empty value (`?name=`) is `None` instead.
+///
+/// Values of forms are text, so the inner value is deserialized from the text like
+/// `serde_urlencoded` does, e.g. by parsing numbers.
+pub mod form {
+    use super::OptionLike;
+    use core::{fmt, marker::PhantomData};
+    use serde::de::{self, value, IntoDeserializer, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+
+    /// Deserialize an optional value, where an empty value is `None`
+    pub fn deserialize<'de, D, O>(deserializer: D) -> Result<O, D::Error>
+    where
+        D: Deserializer<'de>,
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        deserializer.deserialize_option(FormVisitor(PhantomData))
+    }
+
+    /// Serialize an optional value, where `None` is an empty value
+    pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        O: OptionLike,
+        O::Inner: Serialize,
+        S: Serializer,
+    {
+        match option.as_option() {
+            None => serializer.serialize_str(""),
+            option => option.serialize(serializer),
+        }
+    }
+
+    /// Deserialize a value, or its default for an empty value, used for
+    /// `#[nullable(as_default)]`
+    pub fn deserialize_or_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + Default,
+    {
+        deserialize(deserializer).map(Option::unwrap_or_default)
+    }
+
+    /// The form equivalent of [`super::double_option`], where a missing value is `None`, an
+    /// empty value is `Some(None)`, and other values are `Some(Some(T))`
+    pub mod double_option {
+        use super::OptionLike;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a value which is present, where an empty value is `Some(None)`
+        pub fn deserialize<'de, D, O>(deserializer: D) -> Result<O, D::Error>
+        where
+            D: Deserializer<'de>,
+            O: OptionLike,
+            O::Inner: OptionLike,
+            <O::Inner as OptionLike>::Inner: Deserialize<'de>,
+        {
+            super::deserialize(deserializer).map(|inner| O::from_option(Some(inner)))
+        }
+
+        /// Serialize the inner `Option`, where `None` is an empty value. The outer `None` is
+        /// expected to be skipped with `skip_serializing_if`.
+        pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            O: OptionLike,
+            O::Inner: OptionLike,
+            <O::Inner as OptionLike>::Inner: Serialize,
+            S: Serializer,
+        {
+            match option.as_option() {
+                Some(inner) => super::serialize(inner, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+
+    /// The form equivalent of the `Patch` implementations, where an empty value is
+    /// `Patch::Null`
+    pub mod patch {
+        use crate::Patch;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a value which is present, where an empty value is `Patch::Null`
+        pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Patch<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+        {
+            super::deserialize(deserializer).map(|value| Patch::from(Some(value)))
+        }
+
+        /// Serialize `Patch::Null` as an empty value. `Patch::Absent` is expected to be skipped
+        /// with `skip_serializing_if`.
+        pub fn serialize<T, S>(patch: &Patch<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Serialize,
+            S: Serializer,
+        {
+            super::serialize(&patch.as_ref().into_option(), serializer)
+        }
+    }
+
+    /// Deserializes the inner value of a present value, which is `None` if it is empty
+    struct FormVisitor<O>(PhantomData<O>);
+
+    impl<'de, O> Visitor<'de> for FormVisitor<O>
+    where
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        type Value = O;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an optional value")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<O, D::Error> {
+            deserializer.deserialize_any(ValueVisitor(PhantomData))
+        }
+    }
+
+    /// Deserializes the inner value from any value. Only text is parsed, where an empty value
+    /// is `None`, other values are forwarded as they are, e.g. for JSON.
+    struct ValueVisitor<O>(PhantomData<O>);
+
+    impl<O: OptionLike> ValueVisitor<O> {
+        fn inner<'de, D>(deserializer: D) -> Result<O, D::Error>
+        where
+            D: Deserializer<'de>,
+            O::Inner: Deserialize<'de>,
+        {
+            O::Inner::deserialize(deserializer).map(|value| O::from_option(Some(value)))
+        }
+
+        fn text<'de, E: de::Error>(text: Cow<'de, str>) -> Result<O, E>
+        where
+            O::Inner: Deserialize<'de>,
+        {
+            if text.is_empty() {
+                return Ok(O::from_option(None));
+            }
+            Self::inner(FormValue {
+                text,
+                marker: PhantomData,
+            })
+        }
+    }
+
+    macro_rules! forward_value {
+        ($($visit:ident($type_:ty),)*) => {
+            $(
+                fn $visit<E: de::Error>(self, value: $type_) -> Result<O, E> {
+                    Self::inner(IntoDeserializer::<'de, E>::into_deserializer(value))
+                }
+            )*
+        };
+    }
+
+    impl<'de, O> Visitor<'de> for ValueVisitor<O>
+    where
+        O: OptionLike,
+        O::Inner: Deserialize<'de>,
+    {
+        type Value = O;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an optional value")
+        }
+
+        fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<O, E> {
+            Self::text(Cow::Borrowed(value))
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<O, E> {
+            Self::text(Cow::Owned(value.to_owned()))
+        }
+
+        fn visit_string<E: de::Error>(self, value: String) -> Result<O, E> {
+            Self::text(Cow::Owned(value))
+        }
+
+        forward_value! {
+            visit_bool(bool),
+            visit_i64(i64),
+            visit_i128(i128),
+            visit_u64(u64),
+            visit_u128(u128),
+            visit_f64(f64),
+            visit_char(char),
+        }
+
+        fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<O, E> {
+            Self::inner(value::BytesDeserializer::new(value))
+        }
+
+        fn visit_borrowed_bytes<E: de::Error>(self, value: &'de [u8]) -> Result<O, E> {
+            Self::inner(value::BorrowedBytesDeserializer::new(value))
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<O, E> {
+            Ok(O::from_option(None))
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<O, D::Error> {
+            Self::inner(deserializer)
+        }
+
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<O, D::Error> {
+            Self::inner(deserializer)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<O, A::Error> {
+            Self::inner(value::SeqAccessDeserializer::new(seq))
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<O, A::Error> {
+            Self::inner(value::MapAccessDeserializer::new(map))
+        }
+
+        fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<O, A::Error> {
+            Self::inner(value::EnumAccessDeserializer::new(data))
+        }
+    }
+
+    /// Deserializes a value from the text of a form value, parsing booleans and numbers
+    pub(super) struct FormValue<'de, E> {
+        pub(super) text: Cow<'de, str>,
+        pub(super) marker: PhantomData<E>,
+    }
+
+    macro_rules! parse_value {
+        ($($method:ident => $visit:ident,)*) => {
+            $(
+                fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+                    visitor.$visit(self.text.parse().map_err(E::custom)?)
+                }
+            )*
+        };
+    }
+
+    impl<'de, E: de::Error> Deserializer<'de> for FormValue<'de, E> {
+        type Error = E;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+            match self.text {
+                Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+                Cow::Owned(text) => visitor.visit_string(text),
+            }
+        }
+
+        parse_value! {
+            deserialize_bool => visit_bool,
+            deserialize_i8 => visit_i8,
+            deserialize_i16 => visit_i16,
+            deserialize_i32 => visit_i32,
+            deserialize_i64 => visit_i64,
+            deserialize_i128 => visit_i128,
+            deserialize_u8 => visit_u8,
+            deserialize_u16 => visit_u16,
+            deserialize_u32 => visit_u32,
+            deserialize_u64 => visit_u64,
+            deserialize_u128 => visit_u128,
+            deserialize_f32 => visit_f32,
+            deserialize_f64 => visit_f64,
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+            visitor.visit_some(self)
+        }
+
+        fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+            visitor.visit_unit()
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, E> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, E> {
+            self.text
+                .into_deserializer()
+                .deserialize_enum(name, variants, visitor)
+        }
+
+        serde::forward_to_deserialize_any! {
+            char str string bytes byte_buf unit_struct seq tuple tuple_struct map struct
+            identifier ignored_any
+        }
+    }
+}
+
+/// Explicit presence tags for `#[serde_option(binary_safe)]`, so that formats such as
+/// `bincode` or `postcard`, which read the fields by position, can read every field.
+///
+/// Fields are never skipped there, `#[not_required]` fields are (de)serialized with
+/// [`nullable`], and `Patch` fields as an `Option` of everything they can hold.
+pub mod binary_safe {
+    /// `Patch` as an `Option<Option<T>>`, so that `Patch::Absent` is kept
+    pub mod patch {
+        use crate::Patch;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a `Patch`, including `Patch::Absent`
+        pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Patch<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+        {
+            Option::<Option<T>>::deserialize(deserializer).map(Patch::from)
+        }
+
+        /// Serialize a `Patch`, including `Patch::Absent`
+        pub fn serialize<T, S>(patch: &Patch<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Serialize,
+            S: Serializer,
+        {
+            Option::<Option<&T>>::from(patch.as_ref()).serialize(serializer)
+        }
+    }
+}
+
+/// `xsi:nil` for `#[serde_option(xml)]`, where `None` is an element with `xsi:nil="true"`, as
+/// defined by XML Schema, instead of an empty element.
+///
+/// `quick-xml` already deserializes such elements as `None`, as long as the `xsi` prefix is
+/// declared, so only serialization differs from the regular helpers. The namespace is declared
+/// on the element itself, so that the output doesn't depend on a declaration on the root.
+#[cfg(feature = "xml")]
+pub mod xml {
+    use super::OptionLike;
+    use serde::ser::SerializeStruct;
+    use serde::{Serialize, Serializer};
+
+    pub use super::nullable::deserialize;
+
+    /// The XML Schema instance namespace, which `xsi:nil` belongs to
+    const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+    /// An element with `xsi:nil="true"`, written as a struct whose fields are attributes, as
+    /// marked by `quick-xml` with the `@` prefix
+    struct Nil;
+
+    impl Serialize for Nil {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut nil = serializer.serialize_struct("Nil", 2)?;
+            nil.serialize_field("@xmlns:xsi", XSI_NAMESPACE)?;
+            nil.serialize_field("@xsi:nil", &true)?;
+            nil.end()
+        }
+    }
+
+    /// Serialize an optional value, where `None` is an element with `xsi:nil="true"`
+    pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        O: OptionLike,
+        O::Inner: Serialize,
+        S: Serializer,
+    {
+        match option.as_option() {
+            Some(value) => serializer.serialize_some(value),
+            None => Nil.serialize(serializer),
+        }
+    }
+
+    /// The XML equivalent of [`super::double_option`], where `Some(None)` is an element with
+    /// `xsi:nil="true"`, and `None` is a missing element
+    pub mod double_option {
+        use super::OptionLike;
+        use serde::{Serialize, Serializer};
+
+        pub use super::super::double_option::deserialize;
+
+        /// Serialize the inner `Option`, where `None` is an element with `xsi:nil="true"`. The
+        /// outer `None` is expected to be skipped with `skip_serializing_if`.
+        pub fn serialize<O, S>(option: &O, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            O: OptionLike,
+            O::Inner: OptionLike,
+            <O::Inner as OptionLike>::Inner: Serialize,
+            S: Serializer,
+        {
+            match option.as_option() {
+                Some(inner) => super::serialize(inner, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+
+    /// The XML equivalent of the `Patch` implementations, where `Patch::Null` is an element with
+    /// `xsi:nil="true"`
+    pub mod patch {
+        use crate::Patch;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserialize a `Patch` which is present, where `xsi:nil="true"` is `Patch::Null`
+        pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Patch<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+        {
+            Patch::deserialize(deserializer)
+        }
+
+        /// Serialize `Patch::Null` as an element with `xsi:nil="true"`. `Patch::Absent` is
+        /// expected to be skipped with `skip_serializing_if`.
+        pub fn serialize<T, S>(patch: &Patch<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Serialize,
+            S: Serializer,
+        {
+            match patch {
+                Patch::Value(value) => serializer.serialize_some(value),
+                Patch::Null => super::Nil.serialize(serializer),
+                Patch::Absent => serializer.serialize_none(),
+            }
+        }
+    }
+}
+
+/// Schema types used in the `#[schemars(with = "...")]` attributes generated with
+/// `#[serde_option(schemars)]`
+#[cfg(feature = "schemars")]
+pub mod schemars {
+    use core::marker::PhantomData;
+    use schemars::{JsonSchema, Schema, SchemaGenerator};
+    use std::borrow::Cow;
+
+    /// Has the same schema as `Option<T>`, but isn't treated as an `Option` by `schemars`.
+    ///
+    /// `schemars` considers `Option` fields without `#[serde(default)]` to be optional, while
+    /// serde requires `#[nullable]` fields to be present.
+    pub struct Nullable<T>(PhantomData<T>);
+
+    impl<T: JsonSchema> JsonSchema for Nullable<T> {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            Option::<T>::schema_name()
+        }
+
+        fn schema_id() -> Cow<'static, str> {
+            Option::<T>::schema_id()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            Option::<T>::json_schema(generator)
+        }
+    }
+}
+
+/// Schema helpers used in the `#[schema(...)]` attributes generated with
+/// `#[serde_option(utoipa(openapi = "3.0"))]`
+#[cfg(feature = "utoipa")]
+pub mod utoipa {
+    use serde::Serialize;
+    use utoipa::openapi::extensions::Extensions;
+    use utoipa::openapi::schema::{AllOf, SchemaType, Type};
+    use utoipa::PartialSchema;
+
+    pub use utoipa::openapi::{schema::Schema, RefOr};
+
+    /// The schema of `T`, marked with OpenAPI 3.0's `nullable: true`.
+    ///
+    /// `utoipa` only supports OpenAPI 3.1, where `null` is part of the `type` instead. The
+    /// keyword is therefore added through the extensions of the schema, which are serialized
+    /// as-is. References can't have sibling keywords in OpenAPI 3.0, so they are wrapped in an
+    /// `allOf`.
+    pub fn nullable<T: PartialSchema>() -> RefOr<Schema> {
+        with_keywords::<T>(Extensions::from_iter([("nullable", true)]))
+    }
+
+    /// [`nullable`], with the value returned by `default` as its default. `utoipa` ignores
+    /// `default = ...` for fields with `schema_with`, so it is added as a keyword as well.
+    pub fn nullable_with_default<T, D>(default: impl FnOnce() -> D) -> RefOr<Schema>
+    where
+        T: PartialSchema,
+        D: Serialize,
+    {
+        let mut keywords = Extensions::from_iter([("nullable", true)]);
+        if let Ok(default) = serde_json::to_value(default()) {
+            keywords.merge(Extensions::from_iter([("default", default)]));
+        }
+        with_keywords::<T>(keywords)
+    }
+
+    /// The schema of `T`, with `keywords` added through its extensions
+    fn with_keywords<T: PartialSchema>(keywords: Extensions) -> RefOr<Schema> {
+        let mut schema = match T::schema() {
+            RefOr::Ref(reference) => {
+                let mut all_of = AllOf::new();
+                all_of.items.push(RefOr::Ref(reference));
+                Schema::AllOf(all_of)
+            }
+            RefOr::T(schema) => schema,
+        };
+        let extensions = match &mut schema {
+            Schema::Object(object) => {
+                remove_null(&mut object.schema_type);
+                &mut object.extensions
+            }
+            Schema::Array(array) => {
+                remove_null(&mut array.schema_type);
+                &mut array.extensions
+            }
+            Schema::OneOf(one_of) => &mut one_of.extensions,
+            Schema::AllOf(all_of) => &mut all_of.extensions,
+            Schema::AnyOf(any_of) => &mut any_of.extensions,
+            _ => return RefOr::T(schema),
+        };
+        extensions
+            .get_or_insert_with(Extensions::default)
+            .merge(keywords);
+        RefOr::T(schema)
+    }
+
+    /// Remove `null` from a type array, which is not valid in OpenAPI 3.0
+    fn remove_null(schema_type: &mut SchemaType) {
+        if let SchemaType::Array(types) = schema_type {
+            types.retain(|type_| *type_ != Type::Null);
+            if let [type_] = types.as_slice() {
+                *schema_type = SchemaType::Type(type_.clone());
+            }
+        }
+    }
+}
+
+/// Used in the code generated with `#[serde_option(json_schema)]` and the `validate` feature
+#[cfg(any(feature = "json_schema", feature = "validate"))]
+pub use serde_json;
+
+/// Schema builders used in the code generated with `#[serde_option(json_schema)]`
+#[cfg(feature = "json_schema")]
+pub mod json_schema {
+    use serde_json::{json, Map, Value};
+
+    /// The schema of a struct or struct variant, given its `(name, schema, required)` properties
+    pub fn object(properties: Vec<(&str, Value, bool)>, deny_unknown_fields: bool) -> Value {
+        let required: Vec<&str> = properties
+            .iter()
+            .filter(|(_, _, required)| *required)
+            .map(|(name, _, _)| *name)
+            .collect();
+        let properties: Map<String, Value> = properties
+            .into_iter()
+            .map(|(name, schema, _)| (name.to_owned(), schema))
+            .collect();
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = required.into();
+        }
+        if deny_unknown_fields {
+            schema["additionalProperties"] = false.into();
+        }
+        schema
+    }
+
+    /// The schema of a tuple, tuple struct or tuple variant
+    pub fn tuple(items: Vec<Value>) -> Value {
+        let len = items.len();
+        json!({
+            "type": "array",
+            "prefixItems": items,
+            "minItems": len,
+            "maxItems": len,
+        })
+    }
+
+    /// The schema of a unit struct, or of a unit variant of an untagged enum
+    pub fn unit() -> Value {
+        json!({ "type": "null" })
+    }
+
+    /// The schema of an enum, given the schemas of its variants
+    pub fn one_of(variants: Vec<Value>) -> Value {
+        json!({ "oneOf": variants })
+    }
+
+    /// An externally tagged variant, i.e. `"name"` for unit variants and `{"name": ...}`
+    /// otherwise
+    pub fn external(name: &str, body: Option<Value>) -> Value {
+        match body {
+            None => json!({ "const": name }),
+            Some(body) => json!({
+                "type": "object",
+                "properties": { name: body },
+                "required": [name],
+                "additionalProperties": false,
+            }),
+        }
+    }
+
+    /// An internally tagged variant, i.e. `{"tag": "name", ...}`
+    pub fn internal(tag: &str, name: &str, body: Option<Value>) -> Value {
+        let tagged = json!({
+            "type": "object",
+            "properties": { tag: { "const": name } },
+            "required": [tag],
+        });
+        match body {
+            None => tagged,
+            // The tag is added to the properties of struct variants
+            Some(mut body) if body.get("properties").is_some_and(Value::is_object) => {
+                body["properties"][tag] = json!({ "const": name });
+                match body.get_mut("required").and_then(Value::as_array_mut) {
+                    Some(required) => required.insert(0, tag.into()),
+                    None => body["required"] = json!([tag]),
+                }
+                body
+            }
+            Some(body) => json!({ "allOf": [tagged, body] }),
+        }
+    }
+
+    /// An adjacently tagged variant, i.e. `{"tag": "name", "content": ...}`
+    pub fn adjacent(tag: &str, content: &str, name: &str, body: Option<Value>) -> Value {
+        let mut schema = json!({
+            "type": "object",
+            "properties": { tag: { "const": name } },
+            "required": [tag],
+            "additionalProperties": false,
+        });
+        if let Some(body) = body {
+            schema["properties"][content] = body;
+            schema["required"] = json!([tag, content]);
+        }
+        schema
+    }
+}
+
+/// Helpers used in the `ValidatePresence` implementations generated with the `validate` feature
+#[cfg(feature = "validate")]
+pub mod validate {
+    use crate::error::pointer;
+    use crate::validate::{PresenceError, PresenceErrorKind, ValidatePresence};
+    use core::marker::PhantomData;
+    use serde_json::{Map, Value};
+    use std::borrow::Cow;
+
+    /// Validates nested values of type `T`, if `T` implements `ValidatePresence`.
+    ///
+    /// The generated code calls the methods on `&&Nested<T>`, so that method resolution picks
+    /// [`ValidateNested`] for types implementing the trait, and falls back to [`SkipNested`]
+    /// otherwise. This way, field types don't need to implement the trait.
+    pub struct Nested<T: ?Sized>(PhantomData<T>);
+
+    impl<T: ?Sized> Nested<T> {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    /// Picked for types implementing `ValidatePresence`
+    pub trait ValidateNested {
+        fn accepts_null(&self) -> bool;
+        fn validate_nested(&self, value: &Value, path: &str, errors: &mut Vec<PresenceError>);
+    }
+
+    impl<T: ValidatePresence + ?Sized> ValidateNested for &Nested<T> {
+        fn accepts_null(&self) -> bool {
+            T::ACCEPTS_NULL
+        }
+
+        fn validate_nested(&self, value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+            T::validate_at(value, path, errors)
+        }
+    }
+
+    /// Picked for other types, which aren't checked, since they might accept `null`
+    pub trait SkipNested {
+        fn accepts_null(&self) -> bool {
+            true
+        }
+
+        fn validate_nested(&self, _: &Value, _: &str, _: &mut Vec<PresenceError>) {}
+    }
+
+    impl<T: ?Sized> SkipNested for Nested<T> {}
+
+    /// Check the presence of a field in `object` under any of its `names`, i.e. its
+    /// deserialized name and its aliases, returning its value and path if it is present and
+    /// not `null`
+    pub fn check_field<'a>(
+        object: &'a Map<String, Value>,
+        path: &str,
+        names: &[&str],
+        required: bool,
+        accepts_null: bool,
+        errors: &mut Vec<PresenceError>,
+    ) -> Option<(&'a Value, String)> {
+        let found = names
+            .iter()
+            .find_map(|name| Some((*name, object.get(*name)?)));
+        let path = pointer(path, found.map_or(names[0], |(name, _)| name));
+        match found.map(|(_, value)| value) {
+            None if required => {
+                errors.push(PresenceError::new(
+                    path,
+                    PresenceErrorKind::MissingRequiredField,
+                ));
+                None
+            }
+            Some(Value::Null) if !accepts_null => {
+                errors.push(PresenceError::new(path, PresenceErrorKind::NullNotAllowed));
+                None
+            }
+            Some(Value::Null) | None => None,
+            Some(value) => Some((value, path)),
+        }
+    }
+
+    /// The item at `index` of a tuple struct or variant, with its path
+    pub fn tuple_item<'a>(
+        value: &'a Value,
+        path: &str,
+        index: usize,
+    ) -> Option<(&'a Value, String)> {
+        let item = value.as_array()?.get(index)?;
+        Some((item, pointer(path, &index.to_string())))
+    }
+
+    /// The variant name, body and path of an externally tagged enum
+    pub fn external<'a>(value: &'a Value, path: &str) -> Option<(&'a str, Cow<'a, Value>, String)> {
+        match value {
+            Value::String(name) => Some((name, Cow::Owned(Value::Null), path.to_owned())),
+            Value::Object(object) if object.len() == 1 => {
+                let (name, body) = object.iter().next()?;
+                Some((name, Cow::Borrowed(body), pointer(path, name)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The variant name, body and path of an internally tagged enum. The tag is removed from
+    /// the body, as it is by serde.
+    pub fn internal<'a>(
+        value: &'a Value,
+        path: &str,
+        tag: &str,
+    ) -> Option<(&'a str, Cow<'a, Value>, String)> {
+        let name = value.get(tag)?.as_str()?;
+        let mut body = value.clone();
+        body.as_object_mut()?.remove(tag);
+        Some((name, Cow::Owned(body), path.to_owned()))
+    }
+
+    /// The variant name, body and path of an adjacently tagged enum
+    pub fn adjacent<'a>(
+        value: &'a Value,
+        path: &str,
+        tag: &str,
+        content: &str,
+    ) -> Option<(&'a str, Cow<'a, Value>, String)> {
+        let name = value.get(tag)?.as_str()?;
+        let body = value
+            .get(content)
+            .map_or(Cow::Owned(Value::Null), Cow::Borrowed);
+        Some((name, body, pointer(path, content)))
+    }
+}
@@ -0,0 +1,213 @@
+//! Check a [`serde_json::Value`] against the presence rules of a type, reporting every
+//! violation at once.
+//!
+//! serde stops deserializing at the first error. With the `validate` feature,
+//! `#[serde_option(validate)]` implements [`ValidatePresence`] for the item, and adds an
+//! inherent `fn validate_presence(&serde_json::Value) -> Result<(), Vec<PresenceError>>`.
+//! It lists every missing required field and every unexpected `null`, with the
+//! [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the offending value.
+//!
+//! ```
+//! # use serde::Deserialize;
+//! use serde_json::json;
+//! use serde_option::serde_option;
+//! use serde_option::validate::{PresenceError, PresenceErrorKind};
+//!
+//! #[serde_option(validate)]
+//! #[derive(Deserialize)]
+//! struct Address {
+//!     #[nullable]
+//!     city: Option<String>,
+//! }
+//!
+//! #[serde_option(validate)]
+//! #[derive(Deserialize)]
+//! struct User {
+//!     #[nullable]
+//!     name: Option<String>,
+//!     #[not_required]
+//!     email: Option<String>,
+//!     addresses: Vec<Address>,
+//! }
+//!
+//! let errors = User::validate_presence(&json!({"email": null, "addresses": [{}]}));
+//! assert_eq!(
+//!     errors.unwrap_err(),
+//!     [
+//!         PresenceError::new("/name", PresenceErrorKind::MissingRequiredField),
+//!         PresenceError::new("/email", PresenceErrorKind::NullNotAllowed),
+//!         PresenceError::new("/addresses/0/city", PresenceErrorKind::MissingRequiredField),
+//!     ]
+//! );
+//! ```
+//!
+//! Only presence is checked, values of the wrong type are left for serde to report. Nested
+//! types are validated if they implement [`ValidatePresence`], e.g. by using the macro as well.
+//! Values of other types aren't checked at all, since they might accept `null`. This includes
+//! fields whose type is a generic parameter. Variants of untagged enums are not validated.
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub use crate::error::pointer;
+use crate::Patch;
+
+/// Alias of [`crate::Error`], as reported by `validate_presence`
+pub use crate::Error as PresenceError;
+/// Alias of [`crate::ErrorKind`]
+pub use crate::ErrorKind as PresenceErrorKind;
+
+/// A type whose presence rules can be checked on a [`serde_json::Value`].
+///
+/// This is implemented by `#[serde_option(validate)]`, for primitive types and strings, and for
+/// containers of the standard library, which validate their items.
+pub trait ValidatePresence {
+    /// Whether the type accepts `null`, e.g. `Option<T>`
+    const ACCEPTS_NULL: bool = false;
+
+    /// Check `value`, which is located at the JSON pointer `path`, and push the violations to
+    /// `errors`
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>);
+}
+
+/// Check `value` against the presence rules of `T`, reporting every violation
+pub fn validate_presence<T: ValidatePresence + ?Sized>(
+    value: &Value,
+) -> Result<(), Vec<PresenceError>> {
+    let mut errors = Vec::new();
+    T::validate_at(value, "", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl<T: ValidatePresence> ValidatePresence for Option<T> {
+    const ACCEPTS_NULL: bool = true;
+
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+        if !value.is_null() {
+            T::validate_at(value, path, errors)
+        }
+    }
+}
+
+impl<T: ValidatePresence> ValidatePresence for Patch<T> {
+    const ACCEPTS_NULL: bool = true;
+
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+        Option::<T>::validate_at(value, path, errors)
+    }
+}
+
+macro_rules! wrapper_validation {
+    ($($type_:ty,)*) => {
+        $(
+            impl<T: ValidatePresence + ?Sized> ValidatePresence for $type_ {
+                const ACCEPTS_NULL: bool = T::ACCEPTS_NULL;
+
+                fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+                    T::validate_at(value, path, errors)
+                }
+            }
+        )*
+    };
+}
+
+wrapper_validation! {
+    &T,
+    Box<T>,
+    Rc<T>,
+    Arc<T>,
+}
+
+macro_rules! array_validation {
+    ($($type_:ty,)*) => {
+        $(
+            impl<T: ValidatePresence> ValidatePresence for $type_ {
+                fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+                    for (index, item) in value.as_array().into_iter().flatten().enumerate() {
+                        T::validate_at(item, &pointer(path, &index.to_string()), errors);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+array_validation! {
+    [T],
+    Vec<T>,
+    VecDeque<T>,
+    LinkedList<T>,
+}
+
+impl<T: ValidatePresence, const N: usize> ValidatePresence for [T; N] {
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+        <[T]>::validate_at(value, path, errors)
+    }
+}
+
+impl<K, V: ValidatePresence> ValidatePresence for BTreeMap<K, V> {
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+        for (key, item) in value.as_object().into_iter().flatten() {
+            V::validate_at(item, &pointer(path, key), errors);
+        }
+    }
+}
+
+impl<K, V: ValidatePresence, S> ValidatePresence for HashMap<K, V, S> {
+    fn validate_at(value: &Value, path: &str, errors: &mut Vec<PresenceError>) {
+        BTreeMap::<K, V>::validate_at(value, path, errors)
+    }
+}
+
+macro_rules! value_validation {
+    ($($type_:ty,)*) => {
+        $(
+            impl ValidatePresence for $type_ {
+                fn validate_at(_: &Value, _: &str, _: &mut Vec<PresenceError>) {}
+            }
+        )*
+    };
+}
+
+value_validation! {
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    str,
+    String,
+}
+
+impl ValidatePresence for Value {
+    const ACCEPTS_NULL: bool = true;
+
+    fn validate_at(_: &Value, _: &str, _: &mut Vec<PresenceError>) {}
+}
+
+impl ValidatePresence for Map<String, Value> {
+    fn validate_at(_: &Value, _: &str, _: &mut Vec<PresenceError>) {}
+}
+
+impl ValidatePresence for () {
+    const ACCEPTS_NULL: bool = true;
+
+    fn validate_at(_: &Value, _: &str, _: &mut Vec<PresenceError>) {}
+}
@@ -0,0 +1,10 @@
+use serde_option::serde_option;
+
+#[serde_option]
+struct Foo {
+    #[nullable]
+    #[not_required(accept_null)]
+    x: Option<Option<u64>>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use serde::Serialize;
+use serde_option::serde_option;
+
+type MyOption<T> = Option<T>;
+
+#[serde_option]
+#[derive(Serialize)]
+struct Foo {
+    #[not_required(accept_null)]
+    x: MyOption<u64>,
+}
+
+fn main() {}
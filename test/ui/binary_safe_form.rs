@@ -0,0 +1,9 @@
+use serde_option::serde_option;
+
+#[serde_option(binary_safe, form)]
+struct Foo {
+    #[nullable]
+    x: Option<u64>,
+}
+
+fn main() {}
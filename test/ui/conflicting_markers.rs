@@ -0,0 +1,10 @@
+use serde_option::serde_option;
+
+#[serde_option(default = not_required)]
+struct Foo {
+    #[required]
+    #[not_required]
+    x: Option<u64>,
+}
+
+fn main() {}
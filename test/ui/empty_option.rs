@@ -0,0 +1,9 @@
+use serde_option::serde_option;
+
+#[serde_option]
+struct Foo {
+    #[not_required(empty)]
+    x: Option<Vec<u64>>,
+}
+
+fn main() {}
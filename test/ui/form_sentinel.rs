@@ -0,0 +1,9 @@
+use serde_option::serde_option;
+
+#[serde_option(form)]
+struct Foo {
+    #[nullable(sentinel = "N/A")]
+    x: Option<u64>,
+}
+
+fn main() {}
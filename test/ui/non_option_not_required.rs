@@ -1,9 +1,14 @@
+use serde::Serialize;
 use serde_option::serde_option;
 
+#[derive(PartialEq, Serialize)]
+struct NoDefault;
+
 #[serde_option]
+#[derive(Serialize)]
 struct Foo {
     #[not_required]
-    x: u64,
+    x: NoDefault,
 }
 
 fn main() {}
@@ -0,0 +1,10 @@
+use serde_option::serde_option;
+
+#[serde_option(null_repr = omit)]
+struct Foo {
+    #[nullable]
+    #[not_required]
+    x: Option<Option<u64>>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use serde_option::serde_option;
+
+#[serde_option]
+struct Foo {
+    #[nullable(sentinel_out)]
+    x: Option<u64>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use serde_option::serde_option;
+
+#[serde_option]
+struct Foo {
+    #[nullable]
+    #[not_required]
+    x: Option<u64>,
+}
+
+#[serde_option(default = both)]
+struct Bar {
+    y: Option<u64>,
+}
+
+fn main() {}
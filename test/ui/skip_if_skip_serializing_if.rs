@@ -0,0 +1,12 @@
+use serde::Serialize;
+use serde_option::serde_option;
+
+#[serde_option]
+#[derive(Serialize)]
+struct Foo {
+    #[not_required(skip_if = "Vec::is_empty")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<Vec<u64>>,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use serde_option::serde_option;
+
+#[serde_option(strict)]
+#[derive(Deserialize, Serialize)]
+struct Foo {
+    #[nullable]
+    annotated: Option<u64>,
+    unannotated: Option<u64>,
+    #[serde(skip)]
+    skipped: Option<u64>,
+}
+
+#[serde_option(strict = warn)]
+#[derive(Deserialize, Serialize)]
+enum Bar {
+    Variant(Option<u64>),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use serde_option::serde_option;
+
+#[serde_option(xml, null_repr = "null")]
+struct Foo {
+    #[nullable]
+    x: Option<u64>,
+}
+
+fn main() {}